@@ -0,0 +1,666 @@
+// Gas used by every entry point of the compiled contract, measured in cosmwasm-vm:
+//
+//     rustup toolchain install nightly --component rust-src
+//     cargo bench --features bench --bench gas
+//
+// Building the contract for the VM takes nightly `-Zbuild-std`, see `WASM_TOOLCHAIN`.
+// The contract is built first, unless `COUNTING_CONTRACT_WASM` points to an already built artifact
+// such as the optimizer's output. Results are compared against `benches/gas_baseline.txt` and the run fails
+// when any call got more than `TOLERANCE_PERCENT` more expensive. Gas depends on the compiler, so
+// record the baseline with the toolchain CI uses, by running with `UPDATE_GAS_BASELINE=1`.
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+use cosmwasm_std::{coin, to_json_binary, Decimal, Timestamp, Uint128};
+use cosmwasm_std_2::{
+    testing::{
+        mock_ibc_channel_close_init, mock_ibc_channel_connect_ack, mock_ibc_channel_open_init,
+        mock_ibc_packet_ack, mock_ibc_packet_recv, mock_ibc_packet_timeout,
+    },
+    to_json_binary as to_vm_binary, Coin as VmCoin, ContractResult, Empty, Env, IbcAcknowledgement,
+    IbcBasicResponse, IbcOrder, IbcReceiveResponse, Response, SystemError, SystemResult, WasmQuery,
+};
+use cosmwasm_vm::{
+    testing::{
+        execute, ibc_channel_close, ibc_channel_connect, ibc_channel_open, ibc_packet_ack,
+        ibc_packet_receive, ibc_packet_timeout, instantiate, migrate, mock_env, mock_info,
+        mock_instance_with_options, query, MockApi, MockInstanceOptions, MockQuerier, MockStorage,
+    },
+    Instance, Storage,
+};
+use counting_contract::msg::{
+    AllowedPrefix, ExecMsg, IbcAck, IbcPacketMsg, InstantiateMsg, MigrateMsg, Parent, QueryMsg,
+    ReceiveMsg, WithdrawalDelay,
+};
+use cw20::{BalanceResponse, Cw20ReceiveMsg};
+
+const TOLERANCE_PERCENT: u64 = 5;
+// The prebuilt wasm std uses post-MVP features cosmwasm-vm rejects, so the contract is built with
+// std rebuilt for the MVP. That takes nightly with the `rust-src` component.
+const WASM_TOOLCHAIN: &str = "+nightly";
+const BASELINE: &str = "benches/gas_baseline.txt";
+
+const ATOM: &str = "atom";
+const IBC_CHANNEL: &str = "channel-0";
+const IBC_VERSION: &str = "counting-1";
+const CONTRACT_BALANCE: u128 = 1_000;
+const CW20_BALANCE: u128 = 100;
+
+fn addr(name: &str) -> String {
+    MockApi::default().addr_make(name)
+}
+
+fn atom(amount: u128) -> Vec<VmCoin> {
+    vec![cosmwasm_std_2::coin(amount, ATOM)]
+}
+
+struct Bench {
+    instance: Instance<MockApi, MockStorage, MockQuerier>,
+    env: Env,
+    gas: Vec<(String, u64)>,
+}
+
+impl Bench {
+    fn new(wasm: &[u8], msg: InstantiateMsg) -> Self {
+        let mut instance = mock_instance_with_options(
+            wasm,
+            MockInstanceOptions {
+                contract_balance: Some(&atom(CONTRACT_BALANCE)),
+                gas_limit: u64::MAX,
+                ..Default::default()
+            },
+        );
+
+        // Only cw20 balances are ever queried from other contracts.
+        instance
+            .with_querier(|querier| {
+                querier.update_wasm(|query| match query {
+                    WasmQuery::Smart { .. } => {
+                        let balance = BalanceResponse {
+                            balance: Uint128::new(CW20_BALANCE),
+                        };
+                        SystemResult::Ok(ContractResult::Ok(to_vm_binary(&balance).unwrap()))
+                    }
+                    _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                        kind: "wasm".to_owned(),
+                    }),
+                });
+                Ok(())
+            })
+            .unwrap();
+
+        let mut bench = Self {
+            instance,
+            env: mock_env(),
+            gas: vec![],
+        };
+
+        bench.measure("instantiate", |instance, env| {
+            let resp: ContractResult<Response<Empty>> =
+                instantiate(instance, env, mock_info(&addr("owner"), &[]), msg);
+            resp.into_result().map(|_| ())
+        });
+
+        bench
+    }
+
+    fn now(&self) -> Timestamp {
+        Timestamp::from_nanos(self.env.block.time.nanos())
+    }
+
+    fn advance(&mut self, seconds: u64) {
+        self.env.block.height += seconds / 5;
+        self.env.block.time = self.env.block.time.plus_seconds(seconds);
+    }
+
+    fn run<F>(&mut self, name: &str, call: F) -> u64
+    where
+        F: FnOnce(&mut Instance<MockApi, MockStorage, MockQuerier>, Env) -> Result<(), String>,
+    {
+        let gas_before = self.instance.get_gas_left();
+        if let Err(err) = call(&mut self.instance, self.env.clone()) {
+            panic!("{name} failed: {err}");
+        }
+        gas_before - self.instance.get_gas_left()
+    }
+
+    fn measure<F>(&mut self, name: &str, call: F)
+    where
+        F: FnOnce(&mut Instance<MockApi, MockStorage, MockQuerier>, Env) -> Result<(), String>,
+    {
+        let gas = self.run(name, call);
+        self.gas.push((name.to_owned(), gas));
+    }
+
+    // Executes without recording gas, for calls only preparing state for a measured one.
+    fn setup(&mut self, sender: &str, msg: ExecMsg, funds: &[VmCoin]) {
+        let name = format!("{msg:?}");
+        self.run(&name, |instance, env| {
+            exec(instance, env, sender, msg, funds)
+        });
+    }
+
+    fn execute(&mut self, name: &str, sender: &str, msg: ExecMsg, funds: &[VmCoin]) {
+        self.measure(name, |instance, env| {
+            exec(instance, env, sender, msg, funds)
+        });
+    }
+
+    fn query(&mut self, name: &str, msg: QueryMsg) {
+        self.measure(name, |instance, env| {
+            query(instance, env, msg).into_result().map(|_| ())
+        });
+    }
+
+    fn migrate(&mut self, name: &str, msg: MigrateMsg) {
+        self.measure(name, |instance, env| {
+            let resp: ContractResult<Response<Empty>> = migrate(instance, env, msg);
+            resp.into_result().map(|_| ())
+        });
+    }
+
+    // Makes the stored contract version look like an older release of this contract.
+    fn rewind_version(&mut self, version: &str) {
+        let info = format!(r#"{{"contract":"counting-contract","version":"{version}"}}"#);
+        self.instance
+            .with_storage(|storage| {
+                storage
+                    .set(b"contract_info", info.as_bytes())
+                    .0
+                    .map_err(Into::into)
+            })
+            .unwrap();
+    }
+}
+
+fn exec(
+    instance: &mut Instance<MockApi, MockStorage, MockQuerier>,
+    env: Env,
+    sender: &str,
+    msg: ExecMsg,
+    funds: &[VmCoin],
+) -> Result<(), String> {
+    let resp: ContractResult<Response<Empty>> =
+        execute(instance, env, mock_info(&addr(sender), funds), msg);
+    resp.into_result().map(|_| ())
+}
+
+fn donate_remote_packet(id: u64) -> IbcPacketMsg {
+    IbcPacketMsg::Donate {
+        id,
+        donor: addr("donor"),
+        funds: vec![coin(10, ATOM)],
+    }
+}
+
+// Walks a contract with a parent through every exec message, then runs every query against the
+// resulting state. Calls are ordered so each one finds the state it needs.
+fn counting(wasm: &[u8]) -> Vec<(String, u64)> {
+    let mut msg = InstantiateMsg::new(
+        0,
+        coin(10, ATOM),
+        Some(Parent {
+            addr: addr("parent"),
+            donating_period: 2,
+            part: Decimal::percent(10),
+            remote: None,
+        }),
+    );
+    msg.allowed_prefixes = vec![AllowedPrefix {
+        prefix: "osmo".to_owned(),
+        lengths: vec![20],
+        channel: "channel-1".to_owned(),
+    }];
+    let mut bench = Bench::new(wasm, msg);
+
+    bench.execute("increment", "donor", ExecMsg::Increment { value: 1 }, &[]);
+    bench.execute("reset", "owner", ExecMsg::Reset { value: 0 }, &[]);
+
+    let donate = ExecMsg::Donate { campaign: None };
+    bench.execute("donate", "donor", donate.clone(), &atom(10));
+    bench.execute("donate_parent_payout", "donor", donate.clone(), &atom(10));
+    bench.execute("donate_below_minimal", "donor", donate.clone(), &atom(5));
+
+    bench.execute(
+        "set_refund_grace_period",
+        "owner",
+        ExecMsg::SetRefundGracePeriod { period: Some(3600) },
+        &[],
+    );
+    bench.execute("donate_refundable", "donor", donate.clone(), &atom(10));
+    bench.execute(
+        "refund_donation",
+        "donor",
+        ExecMsg::RefundDonation { id: 0 },
+        &[],
+    );
+    bench.setup("owner", ExecMsg::SetRefundGracePeriod { period: None }, &[]);
+
+    let now = bench.now();
+    bench.execute(
+        "create_campaign",
+        "owner",
+        ExecMsg::CreateCampaign {
+            targets: vec![coin(100, ATOM)],
+            start_time: now,
+            end_time: now.plus_seconds(100),
+            refundable: true,
+        },
+        &[],
+    );
+    bench.execute(
+        "donate_campaign",
+        "donor",
+        ExecMsg::Donate { campaign: Some(0) },
+        &atom(10),
+    );
+    bench.advance(200);
+    bench.execute(
+        "claim_refund",
+        "donor",
+        ExecMsg::ClaimRefund { campaign: 0 },
+        &[],
+    );
+
+    let now = bench.now();
+    bench.execute(
+        "configure_matching",
+        "owner",
+        ExecMsg::ConfigureMatching {
+            denom: ATOM.to_owned(),
+            ratio: Decimal::one(),
+            per_donor_cap: Uint128::new(100),
+            global_cap: Uint128::new(1_000),
+            ends_at: now.plus_seconds(1_000),
+        },
+        &[],
+    );
+    bench.execute(
+        "fund_matching_pool",
+        "owner",
+        ExecMsg::FundMatchingPool {},
+        &atom(100),
+    );
+    bench.execute("donate_matched", "donor", donate.clone(), &atom(10));
+
+    bench.execute(
+        "allow_cw20",
+        "owner",
+        ExecMsg::AllowCw20 {
+            token: addr("token"),
+            minimal_donation: Uint128::new(10),
+        },
+        &[],
+    );
+    bench.execute(
+        "receive",
+        "token",
+        ExecMsg::Receive(Cw20ReceiveMsg {
+            sender: addr("donor"),
+            amount: Uint128::new(10),
+            msg: to_json_binary(&ReceiveMsg::Donate {}).unwrap(),
+        }),
+        &[],
+    );
+
+    bench.execute(
+        "set_receipt_contract",
+        "owner",
+        ExecMsg::SetReceiptContract {
+            contract: Some(addr("receipt")),
+        },
+        &[],
+    );
+    bench.execute("donate_receipt", "donor", donate, &atom(10));
+
+    bench.execute(
+        "set_allowed_prefixes",
+        "owner",
+        ExecMsg::SetAllowedPrefixes {
+            prefixes: vec![AllowedPrefix {
+                prefix: "osmo".to_owned(),
+                lengths: vec![20, 32],
+                channel: "channel-1".to_owned(),
+            }],
+        },
+        &[],
+    );
+
+    bench.measure("ibc_channel_open", |instance, env| {
+        let msg = mock_ibc_channel_open_init(IBC_CHANNEL, IbcOrder::Unordered, IBC_VERSION);
+        ibc_channel_open(instance, env, msg)
+            .into_result()
+            .map(|_| ())
+    });
+    bench.measure("ibc_channel_connect", |instance, env| {
+        let msg = mock_ibc_channel_connect_ack(IBC_CHANNEL, IbcOrder::Unordered, IBC_VERSION);
+        let resp: ContractResult<IbcBasicResponse<Empty>> = ibc_channel_connect(instance, env, msg);
+        resp.into_result().map(|_| ())
+    });
+
+    let donate_remote = ExecMsg::DonateRemote {
+        channel: IBC_CHANNEL.to_owned(),
+    };
+    bench.execute("donate_remote", "donor", donate_remote.clone(), &atom(10));
+    bench.setup("donor", donate_remote, &atom(10));
+    bench.measure("ibc_packet_receive", |instance, env| {
+        let msg = mock_ibc_packet_recv(IBC_CHANNEL, &donate_remote_packet(0)).unwrap();
+        let resp: ContractResult<IbcReceiveResponse<Empty>> =
+            ibc_packet_receive(instance, env, msg);
+        resp.into_result().map(|_| ())
+    });
+    bench.measure("ibc_packet_ack", |instance, env| {
+        let ack = IbcAck::Result(to_json_binary(&1u64).unwrap());
+        let ack = IbcAcknowledgement::new(to_vm_binary(&ack).unwrap());
+        let msg = mock_ibc_packet_ack(IBC_CHANNEL, &donate_remote_packet(0), ack).unwrap();
+        let resp: ContractResult<IbcBasicResponse<Empty>> = ibc_packet_ack(instance, env, msg);
+        resp.into_result().map(|_| ())
+    });
+    bench.measure("ibc_packet_timeout", |instance, env| {
+        let msg = mock_ibc_packet_timeout(IBC_CHANNEL, &donate_remote_packet(1)).unwrap();
+        let resp: ContractResult<IbcBasicResponse<Empty>> = ibc_packet_timeout(instance, env, msg);
+        resp.into_result().map(|_| ())
+    });
+
+    bench.execute("withdraw", "owner", ExecMsg::Withdraw {}, &[]);
+    bench.execute(
+        "withdraw_to",
+        "owner",
+        ExecMsg::WithdrawTo {
+            receiver: addr("receiver"),
+            funds: vec![coin(10, ATOM)],
+            cw20_funds: vec![],
+        },
+        &[],
+    );
+    bench.execute(
+        "withdraw_split",
+        "owner",
+        ExecMsg::WithdrawSplit {
+            recipients: vec![
+                (addr("receiver"), Decimal::percent(50)),
+                (addr("other_receiver"), Decimal::percent(50)),
+            ],
+            funds: vec![],
+        },
+        &[],
+    );
+
+    let now = bench.now();
+    bench.execute(
+        "create_stream",
+        "owner",
+        ExecMsg::CreateStream {
+            beneficiary: addr("receiver"),
+            denom: ATOM.to_owned(),
+            amount_per_period: Uint128::new(10),
+            period: 60,
+            start_time: now,
+            end_time: now.plus_seconds(600),
+        },
+        &[],
+    );
+    bench.advance(120);
+    bench.execute(
+        "claim_stream",
+        "receiver",
+        ExecMsg::ClaimStream { id: 0 },
+        &[],
+    );
+
+    bench.query("query_value", QueryMsg::Value {});
+    bench.query("query_cw20_tokens", QueryMsg::Cw20Tokens {});
+    bench.query("query_receipt_contract", QueryMsg::ReceiptContract {});
+    bench.query("query_allowed_prefixes", QueryMsg::AllowedPrefixes {});
+    bench.query("query_migration_preview", QueryMsg::MigrationPreview {});
+    bench.query("query_campaign", QueryMsg::Campaign { id: 0 });
+    bench.query("query_campaigns", QueryMsg::Campaigns {});
+    bench.query(
+        "query_campaign_donation",
+        QueryMsg::CampaignDonation {
+            campaign: 0,
+            donor: addr("donor"),
+        },
+    );
+    bench.query("query_matching", QueryMsg::Matching {});
+    bench.query(
+        "query_matched_donation",
+        QueryMsg::MatchedDonation {
+            donor: addr("donor"),
+        },
+    );
+    bench.query("query_stream", QueryMsg::Stream { id: 0 });
+    bench.query("query_streams", QueryMsg::Streams {});
+    bench.query("query_ibc_channels", QueryMsg::IbcChannels {});
+
+    bench.setup(
+        "owner",
+        ExecMsg::SetRefundGracePeriod { period: Some(3600) },
+        &[],
+    );
+    bench.setup("donor", ExecMsg::Donate { campaign: None }, &atom(10));
+    bench.query("query_donation", QueryMsg::Donation { id: 1 });
+    bench.setup(
+        "donor",
+        ExecMsg::DonateRemote {
+            channel: IBC_CHANNEL.to_owned(),
+        },
+        &atom(10),
+    );
+    bench.query("query_ibc_donation", QueryMsg::IbcDonation { id: 2 });
+
+    bench.measure("ibc_channel_close", |instance, env| {
+        let msg = mock_ibc_channel_close_init(IBC_CHANNEL, IbcOrder::Unordered, IBC_VERSION);
+        let resp: ContractResult<IbcBasicResponse<Empty>> = ibc_channel_close(instance, env, msg);
+        resp.into_result().map(|_| ())
+    });
+
+    bench.gas
+}
+
+// Time locked withdrawals need a contract instantiated with a delay, which blocks direct ones.
+fn time_locked(wasm: &[u8]) -> Vec<(String, u64)> {
+    let mut msg = InstantiateMsg::new(0, coin(10, ATOM), None);
+    msg.withdrawal_delay = Some(WithdrawalDelay {
+        delay: 3600,
+        guardian: None,
+    });
+    let mut bench = Bench::new(wasm, msg);
+
+    let request = ExecMsg::RequestWithdrawal {
+        receiver: addr("receiver"),
+        funds: vec![coin(10, ATOM)],
+        cw20_funds: vec![],
+    };
+    bench.execute("request_withdrawal", "owner", request.clone(), &[]);
+    bench.execute(
+        "cancel_withdrawal",
+        "owner",
+        ExecMsg::CancelWithdrawal { id: 0 },
+        &[],
+    );
+    bench.setup("owner", request.clone(), &[]);
+    bench.setup("owner", request, &[]);
+    bench.query(
+        "query_pending_withdrawal",
+        QueryMsg::PendingWithdrawal { id: 1 },
+    );
+    bench.query("query_pending_withdrawals", QueryMsg::PendingWithdrawals {});
+    bench.advance(3600);
+    bench.execute(
+        "execute_withdrawal",
+        "owner",
+        ExecMsg::ExecuteWithdrawal { id: 1 },
+        &[],
+    );
+    bench.query("query_withdrawal_delay", QueryMsg::WithdrawalDelay {});
+    bench.execute(
+        "set_withdrawal_delay",
+        "owner",
+        ExecMsg::SetWithdrawalDelay {
+            delay: 0,
+            guardian: None,
+        },
+        &[],
+    );
+    bench.execute(
+        "cancel_withdrawal_delay_change",
+        "owner",
+        ExecMsg::CancelWithdrawalDelayChange {},
+        &[],
+    );
+
+    bench
+        .gas
+        .into_iter()
+        .map(|(name, gas)| match name.as_str() {
+            "instantiate" => ("instantiate_time_locked".to_owned(), gas),
+            _ => (name, gas),
+        })
+        .collect()
+}
+
+fn wasm() -> Vec<u8> {
+    if let Ok(path) = env::var("COUNTING_CONTRACT_WASM") {
+        return fs::read(&path).unwrap_or_else(|err| panic!("reading {path}: {err}"));
+    }
+
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target_dir = env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| manifest_dir.join("target"))
+        .join("gas-bench");
+
+    // Newer linkers no longer leave the imports provided by the VM undefined on their own.
+    let status = Command::new("cargo")
+        .current_dir(manifest_dir)
+        .env(
+            "RUSTFLAGS",
+            "-C target-cpu=mvp -C link-arg=--allow-undefined",
+        )
+        .args([
+            WASM_TOOLCHAIN,
+            "build",
+            "--release",
+            "--lib",
+            "--target",
+            "wasm32-unknown-unknown",
+            "-Zbuild-std=std,panic_abort",
+            "--target-dir",
+        ])
+        .arg(&target_dir)
+        .status()
+        .expect("running cargo");
+    assert!(status.success(), "building the contract failed");
+
+    let path = target_dir.join("wasm32-unknown-unknown/release/counting_contract.wasm");
+    fs::read(&path).unwrap_or_else(|err| panic!("reading {}: {err}", path.display()))
+}
+
+fn load_baseline(path: &Path) -> Vec<(String, u64)> {
+    let Ok(baseline) = fs::read_to_string(path) else {
+        return vec![];
+    };
+
+    baseline
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (name, gas) = line
+                .split_once(char::is_whitespace)
+                .unwrap_or_else(|| panic!("invalid baseline line: {line}"));
+            let gas = gas
+                .trim()
+                .parse()
+                .unwrap_or_else(|_| panic!("invalid baseline line: {line}"));
+            (name.to_owned(), gas)
+        })
+        .collect()
+}
+
+fn save_baseline(path: &Path, gas: &[(String, u64)]) {
+    let width = gas
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or_default();
+    let mut baseline =
+        "# Recorded by `UPDATE_GAS_BASELINE=1 cargo bench --features bench`.\n".to_owned();
+    for (name, gas) in gas {
+        baseline.push_str(&format!("{name:width$} {gas}\n"));
+    }
+    fs::write(path, baseline).unwrap_or_else(|err| panic!("writing {}: {err}", path.display()));
+}
+
+// The 0.2.0 state is a subset of the current one, so rewinding the version is all its migration
+// needs to run again.
+fn migrating(wasm: &[u8]) -> Vec<(String, u64)> {
+    let mut bench = Bench::new(wasm, InstantiateMsg::new(0, coin(10, ATOM), None));
+    bench.setup("donor", ExecMsg::Donate { campaign: None }, &atom(10));
+
+    bench.migrate("migrate_current_version", MigrateMsg::new(None));
+    bench.rewind_version("0.2.0");
+    bench.migrate("migrate", MigrateMsg::new(None));
+
+    bench
+        .gas
+        .into_iter()
+        .filter(|(name, _)| name != "instantiate")
+        .collect()
+}
+
+fn main() {
+    let wasm = wasm();
+    let gas = [counting(&wasm), time_locked(&wasm), migrating(&wasm)].concat();
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(BASELINE);
+    if env::var_os("UPDATE_GAS_BASELINE").is_some() {
+        save_baseline(&path, &gas);
+        println!("recorded {} entries in {}", gas.len(), path.display());
+        return;
+    }
+
+    let baseline = load_baseline(&path);
+    let width = gas
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or_default();
+    let mut failures = vec![];
+
+    println!(
+        "{:width$} {:>14} {:>14} {:>8}",
+        "call", "gas", "baseline", "change"
+    );
+    for (name, gas) in &gas {
+        let Some((_, expected)) = baseline.iter().find(|(base, _)| base == name) else {
+            println!("{name:width$} {gas:>14} {:>14} {:>8}", "-", "-");
+            failures.push(format!("{name}: no baseline"));
+            continue;
+        };
+
+        let change = (*gas as f64 - *expected as f64) / *expected as f64 * 100.0;
+        println!("{name:width$} {gas:>14} {expected:>14} {change:>+7.2}%");
+
+        if *gas * 100 > *expected * (100 + TOLERANCE_PERCENT) {
+            failures.push(format!("{name}: {gas} exceeds {expected} by {change:.2}%"));
+        }
+    }
+
+    for (name, _) in &baseline {
+        if !gas.iter().any(|(measured, _)| measured == name) {
+            failures.push(format!("{name}: in the baseline but not measured"));
+        }
+    }
+
+    if !failures.is_empty() {
+        eprintln!("\ngas check failed, tolerance is {TOLERANCE_PERCENT}%:");
+        for failure in failures {
+            eprintln!("  {failure}");
+        }
+        process::exit(1);
+    }
+}
@@ -0,0 +1,100 @@
+use bech32::{FromBase32, Variant};
+use cosmwasm_std::{Addr, Deps, Order, StdResult, Storage};
+
+use crate::{
+    error::ContractError,
+    msg::AllowedPrefix,
+    state::{AddressPrefix, ADDRESS_PREFIXES},
+};
+
+pub enum Address {
+    Local(Addr),
+    Remote { address: Addr, channel: String },
+}
+
+impl Address {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Address::Local(address) | Address::Remote { address, .. } => address.as_str(),
+        }
+    }
+}
+
+// Addresses with a configured foreign prefix are checked as bech32 here, anything else is left to
+// the chain's own validation.
+pub fn validate(deps: Deps, address: &str) -> Result<Address, ContractError> {
+    let prefix = address.rsplit_once('1').map(|(prefix, _)| prefix);
+
+    match prefix {
+        Some(prefix) if ADDRESS_PREFIXES.has(deps.storage, prefix) => {
+            let (address, prefix) = validate_remote(deps, address)?;
+
+            Ok(Address::Remote {
+                address,
+                channel: prefix.channel,
+            })
+        }
+        _ => deps
+            .api
+            .addr_validate(address)
+            .map(Address::Local)
+            .map_err(|_| ContractError::InvalidAddressErr {
+                address: address.into(),
+            }),
+    }
+}
+
+pub fn validate_remote(deps: Deps, address: &str) -> Result<(Addr, AddressPrefix), ContractError> {
+    let invalid = |reason: String| ContractError::InvalidBech32Err {
+        address: address.into(),
+        reason,
+    };
+
+    if address.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(invalid("address must be lowercase".into()));
+    }
+
+    let (prefix, data, variant) =
+        bech32::decode(address).map_err(|err| invalid(err.to_string()))?;
+
+    if variant != Variant::Bech32 {
+        return Err(invalid("bech32m addresses are not supported".into()));
+    }
+
+    let config = ADDRESS_PREFIXES
+        .may_load(deps.storage, &prefix)?
+        .ok_or(ContractError::UnsupportedPrefixErr { prefix })?;
+
+    let length = Vec::<u8>::from_base32(&data)
+        .map_err(|err| invalid(err.to_string()))?
+        .len() as u32;
+
+    if !config.lengths.contains(&length) {
+        return Err(ContractError::InvalidAddressLengthErr {
+            address: address.into(),
+            length,
+        });
+    }
+
+    Ok((Addr::unchecked(address), config))
+}
+
+pub fn save_prefixes(storage: &mut dyn Storage, prefixes: Vec<AllowedPrefix>) -> StdResult<()> {
+    let old: Vec<_> = ADDRESS_PREFIXES
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+
+    for prefix in old {
+        ADDRESS_PREFIXES.remove(storage, &prefix);
+    }
+
+    for prefix in prefixes {
+        ADDRESS_PREFIXES.save(
+            storage,
+            &prefix.prefix,
+            &AddressPrefix::new(prefix.lengths, prefix.channel),
+        )?;
+    }
+
+    Ok(())
+}
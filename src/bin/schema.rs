@@ -1,3 +1,6 @@
+#[cfg(feature = "cosmwasm_2")]
+extern crate cosmwasm_schema_2 as cosmwasm_schema;
+
 use cosmwasm_schema::write_api;
 use counting_contract::msg::{ExecMsg, InstantiateMsg, QueryMsg};
 
@@ -1,18 +1,31 @@
 use crate::{
+    address,
     error::ContractError,
-    msg::{InstantiateMsg, Parent},
-    state::{ParentDonation, State, PARENT_DONATION, STATE},
+    migration,
+    msg::{InstantiateMsg, MigrateMsg, Parent, StateOverride, StoredState},
+    state::{ParentDonation, State, WithdrawalConfig, PARENT_DONATION, STATE, WITHDRAWAL_CONFIG},
 };
-use cosmwasm_std::{Addr, Coin, DepsMut, MessageInfo, Response, StdResult};
+use cosmwasm_std::{Deps, DepsMut, MessageInfo, Response, StdResult};
 use cw2::{get_contract_version, set_contract_version};
-use cw_storage_plus::Item;
-use serde::{Deserialize, Serialize};
+use semver::Version;
 
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
-const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-pub fn instantiate(deps: DepsMut, info: MessageInfo, msg: InstantiateMsg) -> StdResult<Response> {
+// Names this contract was published under before, their storage is migrated like our own.
+// Deployments with other histories list theirs at build time, comma separated.
+const PREDECESSOR_NAMES: &str = match option_env!("COUNTING_PREDECESSOR_NAMES") {
+    Some(names) => names,
+    None => "counting-contract-v0",
+};
+
+pub fn instantiate(
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    address::save_prefixes(deps.storage, msg.allowed_prefixes)?;
 
     let parent = msg.parent;
 
@@ -27,129 +40,201 @@ pub fn instantiate(deps: DepsMut, info: MessageInfo, msg: InstantiateMsg) -> Std
     )?;
 
     if let Some(parent) = parent {
-        PARENT_DONATION.save(
+        let parent_donation = parent_donation(deps.as_ref(), parent)?;
+        PARENT_DONATION.save(deps.storage, &parent_donation)?;
+    }
+
+    if let Some(withdrawal_delay) = msg.withdrawal_delay {
+        let guardian = withdrawal_delay
+            .guardian
+            .map(|guardian| deps.api.addr_validate(&guardian))
+            .transpose()?;
+
+        WITHDRAWAL_CONFIG.save(
             deps.storage,
-            &ParentDonation::new(
-                deps.api.addr_validate(&parent.addr)?,
-                parent.donating_period,
-                parent.part,
-            ),
+            &WithdrawalConfig::new(withdrawal_delay.delay, guardian),
         )?;
     }
 
     Ok(Response::new())
 }
 
-pub fn migrate(mut deps: DepsMut, parent: Option<Parent>) -> Result<Response, ContractError> {
-    let contract = get_contract_version(deps.storage)?;
+fn rescue_state(
+    deps: DepsMut,
+    to: &Version,
+    force_from_version: Option<String>,
+    state_override: Option<StateOverride>,
+) -> Result<StoredState, ContractError> {
+    match (force_from_version, state_override) {
+        (Some(version), None) => {
+            let from = migration::parse_version(&version)?;
+            Ok(migration::plan(deps.storage, from, to)?.run(deps.storage)?)
+        }
+        (None, Some(state)) => {
+            let owner = deps.api.addr_validate(&state.owner)?;
 
-    if CONTRACT_NAME != contract.contract {
-        return Err(ContractError::InvalidName {
-            contract: CONTRACT_NAME.into(),
-        });
-    }
+            STATE.save(
+                deps.storage,
+                &State::new(
+                    state.counter,
+                    state.minimal_donation.clone(),
+                    owner.clone(),
+                    None,
+                ),
+            )?;
+            PARENT_DONATION.remove(deps.storage);
 
-    let resp = match contract.version.as_str() {
-        "0.1.0" => migrate_0_1_0(deps.branch(), parent).map_err(ContractError::from)?,
-        "0.2.0" => migrate_0_2_0(deps.branch(), parent).map_err(ContractError::from)?,
-        CONTRACT_VERSION => return Ok(Response::new()),
-        version => {
-            return Err(ContractError::InvalidVersion {
-                version: version.into(),
+            Ok(StoredState {
+                counter: state.counter,
+                minimal_donation: state.minimal_donation,
+                owner,
             })
         }
-    };
+        _ => Err(ContractError::MigrationOverrideErr {
+            reason: "force_from_version and state_override can't be combined".into(),
+        }),
+    }
+}
 
-    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+fn parent_donation(deps: Deps, parent: Parent) -> Result<ParentDonation, ContractError> {
+    let address = match parent.remote {
+        Some(_) => address::validate_remote(deps, &parent.addr)?.0,
+        None => deps.api.addr_validate(&parent.addr)?,
+    };
 
-    Ok(resp)
+    Ok(ParentDonation::new(
+        address,
+        parent.donating_period,
+        parent.part,
+        parent.remote,
+    ))
 }
 
-pub fn migrate_0_2_0(deps: DepsMut, parent: Option<Parent>) -> StdResult<Response> {
-    #[derive(Deserialize, Serialize)]
-    struct OldState {
-        counter: u64,
-        minimal_donation: Coin,
-        owner: Addr,
-    }
-
-    const OLD_STATE: Item<OldState> = Item::new("state");
+pub fn migrate(mut deps: DepsMut, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let known_name = |name: &str| {
+        name == CONTRACT_NAME || PREDECESSOR_NAMES.split(',').any(|n| n.trim() == name)
+    };
 
-    let OldState {
-        counter,
-        minimal_donation,
-        owner,
-    } = OLD_STATE.load(deps.storage)?;
+    let contract = match get_contract_version(deps.storage) {
+        Ok(contract) if known_name(&contract.contract) => Some(contract),
+        // Forks taken over with a state override may carry a foreign name or no contract info at
+        // all, the override replaces whatever they stored.
+        _ if msg.state_override.is_some() => None,
+        Ok(contract) => {
+            return Err(ContractError::InvalidName {
+                expected: CONTRACT_NAME.into(),
+                found: contract.contract,
+            })
+        }
+        Err(err) => return Err(err.into()),
+    };
 
-    STATE.save(
-        deps.storage,
-        &State::new(
-            counter,
-            minimal_donation,
-            owner,
-            parent.as_ref().map(|p| p.donating_period),
-        ),
-    )?;
+    let to = migration::parse_version(CONTRACT_VERSION)?;
+    let planned = match &contract {
+        Some(contract) => migration::parse_version(&contract.version)
+            .and_then(|from| migration::plan(deps.storage, from, &to))
+            .map(|plan| (contract, plan)),
+        None => Err(ContractError::NotFound {
+            item: "contract_info".into(),
+        }),
+    };
 
-    if let Some(parent) = parent {
-        PARENT_DONATION.save(
-            deps.storage,
-            &ParentDonation {
-                address: deps.api.addr_validate(&parent.addr)?,
-                donating_parent_period: parent.donating_period,
-                part: parent.part,
-            },
-        )?;
-    }
+    // Overrides are a last resort for state we can't read, they never replace a working path.
+    let rescue = msg.force_from_version.is_some() || msg.state_override.is_some();
+    let before = match planned {
+        Ok((contract, _)) if rescue => {
+            return Err(ContractError::MigrationOverrideErr {
+                reason: format!("version {} migrates without overrides", contract.version),
+            })
+        }
+        Ok((contract, plan)) if plan.from == to => {
+            // A predecessor already at our version still has to take over our name.
+            if contract.contract != CONTRACT_NAME {
+                set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+            }
 
-    Ok(Response::new())
-}
+            return Ok(Response::new());
+        }
+        Ok((_, plan)) => plan.run(deps.storage)?,
+        Err(err) if !rescue => return Err(err),
+        Err(_) => rescue_state(
+            deps.branch(),
+            &to,
+            msg.force_from_version,
+            msg.state_override,
+        )?,
+    };
 
-pub fn migrate_0_1_0(deps: DepsMut, parent: Option<Parent>) -> StdResult<Response> {
-    const COUNTER: Item<u64> = Item::new("counter");
-    const DONATION: Item<Coin> = Item::new("donation");
-    const OWNER: Item<Addr> = Item::new("owner");
+    if let Some(parent) = msg.parent {
+        let parent_donation = parent_donation(deps.as_ref(), parent)?;
 
-    let counter = COUNTER.load(deps.storage)?;
-    let donation = DONATION.load(deps.storage)?;
-    let owner = OWNER.load(deps.storage)?;
+        STATE.update(deps.storage, |mut state| -> StdResult<_> {
+            state.donating_parent = Some(parent_donation.donating_parent_period);
+            Ok(state)
+        })?;
+        PARENT_DONATION.save(deps.storage, &parent_donation)?;
+    }
 
-    STATE.save(
-        deps.storage,
-        &State::new(
-            counter,
-            donation,
-            owner,
-            parent.as_ref().map(|p| p.donating_period),
-        ),
-    )?;
+    migration::check_invariants(deps.as_ref(), &before)?;
 
-    if let Some(parent) = parent {
-        PARENT_DONATION.save(
-            deps.storage,
-            &ParentDonation {
-                address: deps.api.addr_validate(&parent.addr)?,
-                donating_parent_period: parent.donating_period,
-                part: parent.part,
-            },
-        )?;
-    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(Response::new())
 }
 
 pub mod exec {
+    use cosmwasm_schema::cw_serde;
     use cosmwasm_std::{
-        to_binary, BankMsg, Coin, DepsMut, Env, MessageInfo, Response, StdResult, Uint128, WasmMsg,
+        from_json, to_json_binary, Addr, BankMsg, Coin, Decimal, Deps, DepsMut, Env, Event, IbcMsg,
+        IbcTimeout, MessageInfo, Order, Response, StdError, StdResult, Storage, SubMsg, Timestamp,
+        Uint128, WasmMsg,
     };
+    #[cfg(feature = "cosmwasm_2")]
+    use cosmwasm_std::{to_json_string, IbcCallbackRequest, IbcSrcCallback};
+    use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
 
+    #[cfg(feature = "cosmwasm_2")]
+    use super::ibc::PARENT_TRANSFER_REPLY_ID;
+    #[cfg(feature = "cosmwasm_2")]
+    use crate::state::ParentTransfer;
     use crate::{
+        address::{self, Address},
         error::ContractError,
-        msg::{ExecMsg, IncrementResp},
-        state::{State, PARENT_DONATION, STATE},
+        helpers::CountingContractHelper,
+        msg::{
+            AllowedPrefix, CampaignStatus, IbcPacketMsg, IncrementResp, ReceiptMetadata,
+            ReceiveMsg, RemoteParent,
+        },
+        state::{
+            Campaign, Donation, IbcDonation, Matching, ParentDonation, PendingWithdrawal,
+            PendingWithdrawalConfig, State, Stream, WithdrawalConfig, CAMPAIGNS,
+            CAMPAIGN_DONATIONS, CW20_TOKENS, DONATIONS, IBC_CHANNELS, IBC_DONATIONS,
+            MATCHED_DONORS, MATCHING, NEXT_CAMPAIGN_ID, NEXT_DONATION_ID, NEXT_IBC_DONATION_ID,
+            NEXT_STREAM_ID, NEXT_WITHDRAWAL_ID, PARENT_DONATION, PARENT_PAYOUTS,
+            PENDING_WITHDRAWALS, PENDING_WITHDRAWAL_CONFIG, RECEIPT_CONTRACT, RECEIPT_COUNT,
+            REFUND_GRACE_PERIOD, STATE, STREAMS, WITHDRAWAL_CONFIG,
+        },
     };
 
-    pub fn increment(deps: DepsMut, value: u64, info: MessageInfo) -> StdResult<Response> {
+    use super::ibc::PACKET_LIFETIME;
+
+    // Only the mint message of the receipt contract, not worth depending on a whole cw721
+    // contract crate for.
+    #[cw_serde]
+    enum ReceiptMintMsg {
+        Mint {
+            token_id: String,
+            owner: String,
+            token_uri: Option<String>,
+            extension: ReceiptMetadata,
+        },
+    }
+
+    pub fn increment(
+        deps: DepsMut,
+        value: u64,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
         let new_state = STATE.update(deps.storage, |state| -> StdResult<_> {
             Ok(State {
                 counter: state.counter + value,
@@ -161,7 +246,7 @@ pub mod exec {
             .add_attribute("action", "increment")
             .add_attribute("counter", new_state.counter.to_string().as_str())
             .add_attribute("sender", info.sender.as_str())
-            .set_data(to_binary(&IncrementResp::new(new_state.counter))?);
+            .set_data(to_json_binary(&IncrementResp::new(new_state.counter))?);
 
         Ok(resp)
     }
@@ -185,64 +270,180 @@ pub mod exec {
             .add_attribute("action", "reset")
             .add_attribute("counter", state.counter.to_string().as_str())
             .add_attribute("sender", info.sender.as_str())
-            .set_data(to_binary(&IncrementResp::new(state.counter))?);
+            .set_data(to_json_binary(&IncrementResp::new(state.counter))?);
 
         Ok(resp)
     }
 
-    pub fn donate(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
-        let mut state = STATE.load(deps.storage)?;
-        let mut resp = Response::new();
+    pub fn donate(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        campaign: Option<u64>,
+    ) -> Result<Response, ContractError> {
+        if let Some(id) = campaign {
+            attribute_to_campaign(deps.branch(), &env, &info, id)?;
+        }
 
-        if state.minimal_donation.amount.is_zero()
+        let state = STATE.load(deps.storage)?;
+
+        let accepted = state.minimal_donation.amount.is_zero()
             || info.funds.iter().any(|coin| {
                 coin.denom == state.minimal_donation.denom
                     && coin.amount >= state.minimal_donation.amount
-            })
-        {
-            state.counter += 1;
+            });
 
-            if let Some(parent) = &mut state.donating_parent {
-                *parent -= 1;
+        let payouts_before = PARENT_PAYOUTS.may_load(deps.storage)?.unwrap_or_default();
+        let refundable =
+            REFUND_GRACE_PERIOD.may_load(deps.storage)?.is_some() && !info.funds.is_empty();
+        let mut donation = Donation::new(
+            info.sender.clone(),
+            info.funds.clone(),
+            env.block.time,
+            accepted,
+            payouts_before,
+            campaign,
+        );
 
-                if *parent == 0 {
-                    let parent_donation = PARENT_DONATION.load(deps.storage)?;
-                    *parent = parent_donation.donating_parent_period;
+        let (mut resp, matched) =
+            register_donation(deps.branch(), env, state, accepted, info.sender, info.funds)?;
 
-                    let funds: Vec<_> = deps
-                        .querier
-                        .query_all_balances(env.contract.address)?
-                        .into_iter()
-                        .map(|mut coin| {
-                            coin.amount = coin.amount * parent_donation.part;
-                            coin
-                        })
-                        .collect();
-
-                    let msg = WasmMsg::Execute {
-                        contract_addr: parent_donation.address.to_string(),
-                        msg: to_binary(&ExecMsg::Donate {})?,
-                        funds,
-                    };
+        if let Some(id) = campaign {
+            resp = resp.add_attribute("campaign", id.to_string());
+        }
 
-                    resp = resp
-                        .add_message(msg)
-                        .add_attribute("donation_to_parent", parent_donation.address.to_string());
-                }
+        if refundable {
+            if !matched.is_zero() {
+                donation.matched = matched;
+                donation.matching_round = MATCHING.load(deps.storage)?.round;
             }
 
-            STATE.save(deps.storage, &state)?;
+            let id = NEXT_DONATION_ID.may_load(deps.storage)?.unwrap_or_default();
+            NEXT_DONATION_ID.save(deps.storage, &(id + 1))?;
+            DONATIONS.save(deps.storage, id, &donation)?;
+
+            resp = resp.add_attribute("donation_id", id.to_string());
         }
 
-        resp = resp
-            .add_attribute("action", "donate")
-            .add_attribute("counter", state.counter.to_string().as_str())
+        Ok(resp)
+    }
+
+    pub fn set_refund_grace_period(
+        deps: DepsMut,
+        info: MessageInfo,
+        period: Option<u64>,
+    ) -> Result<Response, ContractError> {
+        let owner = STATE.load(deps.storage)?.owner;
+
+        if info.sender != owner {
+            return Err(ContractError::UnauthorizedErr {
+                owner: owner.into(),
+            });
+        }
+
+        match period {
+            Some(period) => REFUND_GRACE_PERIOD.save(deps.storage, &period)?,
+            None => REFUND_GRACE_PERIOD.remove(deps.storage),
+        }
+
+        let resp: Response = Response::new()
+            .add_attribute("action", "set_refund_grace_period")
             .add_attribute("sender", info.sender.as_str());
 
         Ok(resp)
     }
 
-    pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    pub fn refund_donation(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let donation = DONATIONS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::DonationNotFoundErr { id })?;
+
+        if info.sender != donation.donor {
+            return Err(ContractError::UnauthorizedErr {
+                owner: donation.donor.into(),
+            });
+        }
+
+        let grace_period = REFUND_GRACE_PERIOD
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        if env.block.time >= donation.donated_at.plus_seconds(grace_period) {
+            return Err(ContractError::RefundWindowClosedErr { id });
+        }
+
+        let payouts = PARENT_PAYOUTS.may_load(deps.storage)?.unwrap_or_default();
+        if payouts > donation.payouts_before {
+            return Err(ContractError::DonationForwardedErr { id });
+        }
+
+        // Once a campaign ends its donations are settled through ClaimRefund or a withdrawal.
+        if let Some(campaign_id) = donation.campaign {
+            let campaign = CAMPAIGNS.load(deps.storage, campaign_id)?;
+            if campaign.status(env.block.time) != CampaignStatus::Active {
+                return Err(ContractError::CampaignEndedErr { id: campaign_id });
+            }
+        }
+
+        DONATIONS.remove(deps.storage, id);
+
+        if donation.counted {
+            let parent_period = PARENT_DONATION
+                .may_load(deps.storage)?
+                .map(|parent| parent.donating_parent_period);
+
+            STATE.update(deps.storage, |mut state| -> StdResult<_> {
+                state.counter = state.counter.saturating_sub(1);
+                state.donating_parent = state
+                    .donating_parent
+                    .zip(parent_period)
+                    .map(|(left, period)| std::cmp::min(left + 1, period));
+                Ok(state)
+            })?;
+        }
+
+        if !donation.matched.is_zero() {
+            unmatch_donation(deps.storage, &donation)?;
+        }
+
+        if let Some(campaign_id) = donation.campaign {
+            let mut campaign = CAMPAIGNS.load(deps.storage, campaign_id)?;
+            sub_coins(&mut campaign.raised, &donation.funds);
+            CAMPAIGNS.save(deps.storage, campaign_id, &campaign)?;
+
+            let key = (campaign_id, &donation.donor);
+            let mut donated = CAMPAIGN_DONATIONS
+                .may_load(deps.storage, key)?
+                .unwrap_or_default();
+            sub_coins(&mut donated, &donation.funds);
+
+            if donated.is_empty() {
+                CAMPAIGN_DONATIONS.remove(deps.storage, key);
+            } else {
+                CAMPAIGN_DONATIONS.save(deps.storage, key, &donated)?;
+            }
+        }
+
+        let resp: Response = bank_send(Response::new(), &donation.donor, donation.funds)
+            .add_attribute("action", "refund_donation")
+            .add_attribute("donation_id", id.to_string())
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn create_campaign(
+        deps: DepsMut,
+        info: MessageInfo,
+        targets: Vec<Coin>,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        refundable: bool,
+    ) -> Result<Response, ContractError> {
         let owner = STATE.load(deps.storage)?.owner;
 
         if info.sender != owner {
@@ -251,31 +452,143 @@ pub mod exec {
             });
         }
 
-        let contract_balances = deps.querier.query_all_balances(env.contract.address)?;
-        let bank_msg = BankMsg::Send {
-            to_address: owner.to_string(),
-            amount: contract_balances,
-        };
+        if end_time <= start_time {
+            return Err(ContractError::InvalidCampaignErr {
+                reason: "end time must be after start time".into(),
+            });
+        }
+
+        let id = NEXT_CAMPAIGN_ID.may_load(deps.storage)?.unwrap_or_default();
+        NEXT_CAMPAIGN_ID.save(deps.storage, &(id + 1))?;
+
+        CAMPAIGNS.save(
+            deps.storage,
+            id,
+            &Campaign::new(targets, start_time, end_time, refundable),
+        )?;
 
         let resp: Response = Response::new()
-            .add_message(bank_msg)
-            .add_attribute("action", "withdraw")
+            .add_attribute("action", "create_campaign")
+            .add_attribute("campaign", id.to_string())
             .add_attribute("sender", info.sender.as_str());
 
         Ok(resp)
     }
 
-    pub fn withdraw_to(
+    pub fn claim_refund(
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        receiver: String,
-        funds: Vec<Coin>,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let mut campaign = CAMPAIGNS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::CampaignNotFoundErr { id })?;
+
+        if !campaign.refundable {
+            return Err(ContractError::CampaignNotRefundableErr { id });
+        }
+
+        if campaign.status(env.block.time) != CampaignStatus::Failed {
+            return Err(ContractError::CampaignNotFailedErr { id });
+        }
+
+        let donated = CAMPAIGN_DONATIONS
+            .may_load(deps.storage, (id, &info.sender))?
+            .ok_or(ContractError::NothingToRefundErr { id })?;
+        CAMPAIGN_DONATIONS.remove(deps.storage, (id, &info.sender));
+
+        add_coins(&mut campaign.refunded, &donated);
+        CAMPAIGNS.save(deps.storage, id, &campaign)?;
+
+        let resp: Response = bank_send(Response::new(), &info.sender, donated)
+            .add_attribute("action", "claim_refund")
+            .add_attribute("campaign", id.to_string())
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn donate_remote(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        channel: String,
+    ) -> Result<Response, ContractError> {
+        if !IBC_CHANNELS.has(deps.storage, &channel) {
+            return Err(ContractError::UnknownChannelErr { channel });
+        }
+
+        let id = NEXT_IBC_DONATION_ID
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        NEXT_IBC_DONATION_ID.save(deps.storage, &(id + 1))?;
+        IBC_DONATIONS.save(
+            deps.storage,
+            id,
+            &IbcDonation::new(info.sender.clone(), channel.clone(), info.funds.clone()),
+        )?;
+
+        let packet = IbcPacketMsg::Donate {
+            id,
+            donor: info.sender.to_string(),
+            funds: info.funds,
+        };
+
+        let msg = IbcMsg::SendPacket {
+            channel_id: channel.clone(),
+            data: to_json_binary(&packet)?,
+            timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(PACKET_LIFETIME)),
+        };
+
+        let resp = Response::new()
+            .add_message(msg)
+            .add_attribute("action", "donate_remote")
+            .add_attribute("channel", channel)
+            .add_attribute("ibc_donation_id", id.to_string())
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn receive_cw20(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: Cw20ReceiveMsg,
     ) -> Result<Response, ContractError> {
-        // Validate receiver address
-        if deps.api.addr_validate(&receiver).is_err() {
-            return Err(ContractError::InvalidAddressErr { address: receiver });
+        let minimal_donation = CW20_TOKENS
+            .may_load(deps.storage, &info.sender)?
+            .ok_or_else(|| ContractError::UnsupportedCw20Err {
+                token: info.sender.to_string(),
+            })?;
+
+        match from_json(&msg.msg)? {
+            ReceiveMsg::Donate {} => {
+                let state = STATE.load(deps.storage)?;
+                let donor = deps.api.addr_validate(&msg.sender)?;
+                let accepted = msg.amount >= minimal_donation;
+                let amount = vec![Coin::new(
+                    msg.amount.u128(),
+                    format!("cw20:{}", info.sender),
+                )];
+
+                let (resp, _) = register_donation(deps, env, state, accepted, donor, amount)?;
+                Ok(resp)
+            }
         }
+    }
+
+    pub fn allow_cw20(
+        deps: DepsMut,
+        info: MessageInfo,
+        token: String,
+        minimal_donation: Uint128,
+    ) -> Result<Response, ContractError> {
+        let token = match deps.api.addr_validate(&token) {
+            Ok(token) => token,
+            Err(_) => return Err(ContractError::InvalidAddressErr { address: token }),
+        };
 
         let owner = STATE.load(deps.storage)?.owner;
 
@@ -285,41 +598,1798 @@ pub mod exec {
             });
         }
 
-        let mut contract_balances = deps.querier.query_all_balances(env.contract.address)?;
+        CW20_TOKENS.save(deps.storage, &token, &minimal_donation)?;
 
-        if !funds.is_empty() {
-            for coin in &mut contract_balances {
-                let limit = funds
-                    .iter()
-                    .find(|c| c.denom == coin.denom)
-                    .map(|c| c.amount)
-                    .unwrap_or(Uint128::zero());
+        let resp: Response = Response::new()
+            .add_attribute("action", "allow_cw20")
+            .add_attribute("token", token.as_str())
+            .add_attribute("sender", info.sender.as_str());
 
-                coin.amount = std::cmp::min(coin.amount, limit);
+        Ok(resp)
+    }
+
+    pub fn set_receipt_contract(
+        deps: DepsMut,
+        info: MessageInfo,
+        contract: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let owner = STATE.load(deps.storage)?.owner;
+
+        if info.sender != owner {
+            return Err(ContractError::UnauthorizedErr {
+                owner: owner.into(),
+            });
+        }
+
+        let mut resp = Response::new().add_attribute("action", "set_receipt_contract");
+
+        match contract {
+            Some(contract) => {
+                let contract = match deps.api.addr_validate(&contract) {
+                    Ok(contract) => contract,
+                    Err(_) => return Err(ContractError::InvalidAddressErr { address: contract }),
+                };
+
+                RECEIPT_CONTRACT.save(deps.storage, &contract)?;
+                resp = resp.add_attribute("receipt_contract", contract.as_str());
             }
+            None => RECEIPT_CONTRACT.remove(deps.storage),
+        }
+
+        Ok(resp.add_attribute("sender", info.sender.as_str()))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure_matching(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        denom: String,
+        ratio: Decimal,
+        per_donor_cap: Uint128,
+        global_cap: Uint128,
+        ends_at: Timestamp,
+    ) -> Result<Response, ContractError> {
+        let owner = STATE.load(deps.storage)?.owner;
+
+        if info.sender != owner {
+            return Err(ContractError::UnauthorizedErr {
+                owner: owner.into(),
+            });
         }
 
-        let bank_msg = BankMsg::Send {
-            to_address: receiver,
-            amount: contract_balances,
+        let round = match MATCHING.may_load(deps.storage)? {
+            Some(matching) if env.block.time < matching.ends_at => {
+                return Err(ContractError::MatchingActiveErr {});
+            }
+            Some(matching) => matching.round + 1,
+            None => 0,
         };
 
+        if ends_at <= env.block.time {
+            return Err(ContractError::MatchingEndedErr {});
+        }
+
+        MATCHING.save(
+            deps.storage,
+            &Matching::new(denom, ratio, per_donor_cap, global_cap, ends_at, round),
+        )?;
+
         let resp: Response = Response::new()
-            .add_message(bank_msg)
-            .add_attribute("action", "withdraw")
+            .add_attribute("action", "configure_matching")
             .add_attribute("sender", info.sender.as_str());
 
         Ok(resp)
     }
-}
 
-pub mod query {
-    use cosmwasm_std::{Deps, StdResult};
+    pub fn fund_matching_pool(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let owner = STATE.load(deps.storage)?.owner;
+
+        if info.sender != owner {
+            return Err(ContractError::UnauthorizedErr {
+                owner: owner.into(),
+            });
+        }
 
-    use crate::{msg::ValueResp, state::STATE};
+        let mut matching = MATCHING
+            .may_load(deps.storage)?
+            .ok_or(ContractError::NoMatchingErr {})?;
 
-    pub fn value(deps: Deps) -> StdResult<ValueResp> {
-        let value = STATE.load(deps.storage)?.counter;
-        Ok(ValueResp { value })
+        if env.block.time >= matching.ends_at {
+            return Err(ContractError::MatchingEndedErr {});
+        }
+
+        if info.funds.is_empty() || info.funds.iter().any(|c| c.denom != matching.denom) {
+            return Err(ContractError::InvalidMatchingFundsErr {
+                denom: matching.denom,
+            });
+        }
+
+        let funded = info
+            .funds
+            .iter()
+            .fold(Uint128::zero(), |total, coin| total + coin.amount);
+        matching.pool += funded;
+        MATCHING.save(deps.storage, &matching)?;
+
+        let resp: Response = Response::new()
+            .add_attribute("action", "fund_matching_pool")
+            .add_attribute("pool", matching.pool.to_string())
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        let owner = STATE.load(deps.storage)?.owner;
+
+        if info.sender != owner {
+            return Err(ContractError::UnauthorizedErr {
+                owner: owner.into(),
+            });
+        }
+
+        ensure_not_time_locked(deps.as_ref(), &env)?;
+
+        let contract_balances = available_balances(deps.as_ref(), &env, None)?;
+        let cw20_balances = cw20_balances(deps.as_ref(), &env.contract.address)?;
+
+        let resp: Response = bank_send(Response::new(), &owner, contract_balances)
+            .add_messages(cw20_transfers(&owner, cw20_balances)?)
+            .add_attribute("action", "withdraw")
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn withdraw_to(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        receiver: String,
+        funds: Vec<Coin>,
+        cw20_funds: Vec<Cw20Coin>,
+    ) -> Result<Response, ContractError> {
+        let receiver = address::validate(deps.as_ref(), &receiver)?;
+
+        let owner = STATE.load(deps.storage)?.owner;
+
+        if info.sender != owner {
+            return Err(ContractError::UnauthorizedErr {
+                owner: owner.into(),
+            });
+        }
+
+        ensure_not_time_locked(deps.as_ref(), &env)?;
+
+        let withdraw_all = funds.is_empty() && cw20_funds.is_empty();
+
+        let amount = if withdraw_all || !funds.is_empty() {
+            let contract_balances = available_balances(deps.as_ref(), &env, None)?;
+            withdrawal_funds(contract_balances, &funds)?
+        } else {
+            vec![]
+        };
+
+        let cw20_msgs = match &receiver {
+            Address::Local(local) => {
+                let cw20_amount = if withdraw_all || !cw20_funds.is_empty() {
+                    let cw20_balances = cw20_balances(deps.as_ref(), &env.contract.address)?;
+                    cw20_withdrawal_funds(cw20_balances, &cw20_funds)?
+                } else {
+                    vec![]
+                };
+
+                cw20_transfers(local, cw20_amount)?
+            }
+            // Cw20 tokens can't travel over ICS-20, a full withdrawal only moves native funds.
+            Address::Remote { address, .. } => {
+                if !cw20_funds.is_empty() {
+                    return Err(ContractError::RemoteCw20Err {
+                        address: address.into(),
+                    });
+                }
+
+                vec![]
+            }
+        };
+
+        let resp = send_native(Response::new(), &env, receiver, amount).add_messages(cw20_msgs);
+
+        let resp: Response = resp
+            .add_attribute("action", "withdraw")
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn set_allowed_prefixes(
+        deps: DepsMut,
+        info: MessageInfo,
+        prefixes: Vec<AllowedPrefix>,
+    ) -> Result<Response, ContractError> {
+        let owner = STATE.load(deps.storage)?.owner;
+
+        if info.sender != owner {
+            return Err(ContractError::UnauthorizedErr {
+                owner: owner.into(),
+            });
+        }
+
+        address::save_prefixes(deps.storage, prefixes)?;
+
+        let resp: Response = Response::new()
+            .add_attribute("action", "set_allowed_prefixes")
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn withdraw_split(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipients: Vec<(String, Decimal)>,
+        funds: Vec<Coin>,
+    ) -> Result<Response, ContractError> {
+        let mut validated = Vec::with_capacity(recipients.len());
+        for (recipient, share) in recipients {
+            validated.push((address::validate(deps.as_ref(), &recipient)?, share));
+        }
+
+        // Shares are weights, so splits like thirds don't have to be rounded to sum to one.
+        let total_shares = validated
+            .iter()
+            .fold(Decimal::zero(), |total, (_, share)| total + *share);
+        if total_shares.is_zero() {
+            return Err(ContractError::InvalidSharesErr {
+                total: total_shares,
+            });
+        }
+
+        let owner = STATE.load(deps.storage)?.owner;
+
+        if info.sender != owner {
+            return Err(ContractError::UnauthorizedErr {
+                owner: owner.into(),
+            });
+        }
+
+        ensure_not_time_locked(deps.as_ref(), &env)?;
+
+        let contract_balances = available_balances(deps.as_ref(), &env, None)?;
+        let amount = withdrawal_funds(contract_balances, &funds)?;
+
+        let mut payouts: Vec<Vec<Coin>> = vec![vec![]; validated.len()];
+        for coin in amount {
+            let mut distributed = Uint128::zero();
+
+            for ((_, share), payout) in validated.iter().zip(&mut payouts) {
+                let part = coin
+                    .amount
+                    .multiply_ratio(share.atomics(), total_shares.atomics());
+                distributed += part;
+                payout.push(Coin::new(part.u128(), &coin.denom));
+            }
+
+            // Rounding remainder goes to the last recipient, like in parent payouts.
+            if let Some(last) = payouts.last_mut().and_then(|payout| payout.last_mut()) {
+                last.amount += coin.amount - distributed;
+            }
+        }
+
+        let mut resp = Response::new();
+
+        for ((recipient, _), mut payout) in validated.into_iter().zip(payouts) {
+            payout.retain(|coin| !coin.amount.is_zero());
+
+            let paid = payout
+                .iter()
+                .map(Coin::to_string)
+                .collect::<Vec<_>>()
+                .join(",");
+            let event = Event::new("withdraw_split")
+                .add_attribute("recipient", recipient.as_str())
+                .add_attribute("amount", paid);
+
+            resp = send_native(resp, &env, recipient, payout).add_event(event);
+        }
+
+        resp = resp
+            .add_attribute("action", "withdraw_split")
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn request_withdrawal(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        receiver: String,
+        funds: Vec<Coin>,
+        cw20_funds: Vec<Cw20Coin>,
+    ) -> Result<Response, ContractError> {
+        let receiver = match deps.api.addr_validate(&receiver) {
+            Ok(receiver) => receiver,
+            Err(_) => return Err(ContractError::InvalidAddressErr { address: receiver }),
+        };
+
+        let owner = STATE.load(deps.storage)?.owner;
+
+        if info.sender != owner {
+            return Err(ContractError::UnauthorizedErr {
+                owner: owner.into(),
+            });
+        }
+
+        let delay = withdrawal_config(deps.storage, env.block.time)?.delay;
+        let release_at = env.block.time.plus_seconds(delay);
+
+        let id = NEXT_WITHDRAWAL_ID
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        NEXT_WITHDRAWAL_ID.save(deps.storage, &(id + 1))?;
+
+        PENDING_WITHDRAWALS.save(
+            deps.storage,
+            id,
+            &PendingWithdrawal::new(receiver, funds, cw20_funds, release_at),
+        )?;
+
+        let resp: Response = Response::new()
+            .add_attribute("action", "request_withdrawal")
+            .add_attribute("withdrawal_id", id.to_string())
+            .add_attribute("release_at", release_at.to_string())
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn cancel_withdrawal(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        ensure_owner_or_guardian(deps.as_ref(), &env, &info)?;
+
+        let withdrawal = PENDING_WITHDRAWALS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::WithdrawalNotFoundErr { id })?;
+
+        if env.block.time >= withdrawal.release_at {
+            return Err(ContractError::WithdrawalMaturedErr { id });
+        }
+
+        PENDING_WITHDRAWALS.remove(deps.storage, id);
+
+        let resp: Response = Response::new()
+            .add_attribute("action", "cancel_withdrawal")
+            .add_attribute("withdrawal_id", id.to_string())
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn execute_withdrawal(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let withdrawal = PENDING_WITHDRAWALS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::WithdrawalNotFoundErr { id })?;
+
+        if env.block.time < withdrawal.release_at {
+            return Err(ContractError::WithdrawalNotMaturedErr {
+                id,
+                release_at: withdrawal.release_at,
+            });
+        }
+
+        PENDING_WITHDRAWALS.remove(deps.storage, id);
+
+        let withdraw_all = withdrawal.funds.is_empty() && withdrawal.cw20_funds.is_empty();
+
+        let amount = if withdraw_all || !withdrawal.funds.is_empty() {
+            let contract_balances = available_balances(deps.as_ref(), &env, None)?;
+            withdrawal_funds(contract_balances, &withdrawal.funds)?
+        } else {
+            vec![]
+        };
+
+        let cw20_amount = if withdraw_all || !withdrawal.cw20_funds.is_empty() {
+            let cw20_balances = cw20_balances(deps.as_ref(), &env.contract.address)?;
+            cw20_withdrawal_funds(cw20_balances, &withdrawal.cw20_funds)?
+        } else {
+            vec![]
+        };
+
+        let cw20_msgs = cw20_transfers(&withdrawal.receiver, cw20_amount)?;
+
+        let resp: Response = bank_send(Response::new(), withdrawal.receiver, amount)
+            .add_messages(cw20_msgs)
+            .add_attribute("action", "execute_withdrawal")
+            .add_attribute("withdrawal_id", id.to_string())
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn set_withdrawal_delay(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        delay: u64,
+        guardian: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let guardian = match guardian {
+            Some(guardian) => match deps.api.addr_validate(&guardian) {
+                Ok(guardian) => Some(guardian),
+                Err(_) => return Err(ContractError::InvalidAddressErr { address: guardian }),
+            },
+            None => None,
+        };
+
+        let owner = STATE.load(deps.storage)?.owner;
+
+        if info.sender != owner {
+            return Err(ContractError::UnauthorizedErr {
+                owner: owner.into(),
+            });
+        }
+
+        let current = withdrawal_config(deps.storage, env.block.time)?;
+        let config = WithdrawalConfig::new(delay, guardian);
+
+        // A shorter delay or another guardian would let a compromised owner skip the time lock,
+        // so such changes only apply once the current delay has passed.
+        let weakened = current.delay > 0
+            && (config.delay < current.delay
+                || current.guardian.is_some() && config.guardian != current.guardian);
+
+        let mut resp = Response::new().add_attribute("action", "set_withdrawal_delay");
+
+        if weakened {
+            let effective_at = env.block.time.plus_seconds(current.delay);
+            WITHDRAWAL_CONFIG.save(deps.storage, &current)?;
+            PENDING_WITHDRAWAL_CONFIG.save(
+                deps.storage,
+                &PendingWithdrawalConfig::new(config, effective_at),
+            )?;
+
+            resp = resp.add_attribute("effective_at", effective_at.to_string());
+        } else {
+            WITHDRAWAL_CONFIG.save(deps.storage, &config)?;
+            PENDING_WITHDRAWAL_CONFIG.remove(deps.storage);
+        }
+
+        Ok(resp.add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn cancel_withdrawal_delay_change(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        ensure_owner_or_guardian(deps.as_ref(), &env, &info)?;
+
+        match PENDING_WITHDRAWAL_CONFIG.may_load(deps.storage)? {
+            Some(pending) if env.block.time < pending.effective_at => {
+                PENDING_WITHDRAWAL_CONFIG.remove(deps.storage)
+            }
+            _ => return Err(ContractError::NoPendingWithdrawalDelayErr {}),
+        }
+
+        let resp: Response = Response::new()
+            .add_attribute("action", "cancel_withdrawal_delay_change")
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    // A pending change takes over on its own once it is effective.
+    pub(super) fn withdrawal_config(
+        storage: &dyn Storage,
+        now: Timestamp,
+    ) -> StdResult<WithdrawalConfig> {
+        match PENDING_WITHDRAWAL_CONFIG.may_load(storage)? {
+            Some(pending) if pending.effective_at <= now => Ok(pending.config),
+            _ => Ok(WITHDRAWAL_CONFIG.may_load(storage)?.unwrap_or_default()),
+        }
+    }
+
+    fn ensure_owner_or_guardian(
+        deps: Deps,
+        env: &Env,
+        info: &MessageInfo,
+    ) -> Result<(), ContractError> {
+        let owner = STATE.load(deps.storage)?.owner;
+        let guardian = withdrawal_config(deps.storage, env.block.time)?.guardian;
+
+        if info.sender != owner && Some(&info.sender) != guardian.as_ref() {
+            return Err(ContractError::UnauthorizedErr {
+                owner: owner.into(),
+            });
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        beneficiary: String,
+        denom: String,
+        amount_per_period: Uint128,
+        period: u64,
+        start_time: Timestamp,
+        end_time: Timestamp,
+    ) -> Result<Response, ContractError> {
+        let beneficiary = match deps.api.addr_validate(&beneficiary) {
+            Ok(beneficiary) => beneficiary,
+            Err(_) => {
+                return Err(ContractError::InvalidAddressErr {
+                    address: beneficiary,
+                })
+            }
+        };
+
+        let owner = STATE.load(deps.storage)?.owner;
+
+        if info.sender != owner {
+            return Err(ContractError::UnauthorizedErr {
+                owner: owner.into(),
+            });
+        }
+
+        // A stream drains the balance without a withdrawal request, so it is held to the same lock.
+        ensure_not_time_locked(deps.as_ref(), &env)?;
+
+        if amount_per_period.is_zero() {
+            return Err(ContractError::InvalidStreamErr {
+                reason: "amount per period must be positive".into(),
+            });
+        }
+
+        if period == 0 {
+            return Err(ContractError::InvalidStreamErr {
+                reason: "period must be positive".into(),
+            });
+        }
+
+        if end_time <= start_time {
+            return Err(ContractError::InvalidStreamErr {
+                reason: "end time must be after start time".into(),
+            });
+        }
+
+        let id = NEXT_STREAM_ID.may_load(deps.storage)?.unwrap_or_default();
+        NEXT_STREAM_ID.save(deps.storage, &(id + 1))?;
+
+        STREAMS.save(
+            deps.storage,
+            id,
+            &Stream::new(
+                beneficiary.clone(),
+                denom,
+                amount_per_period,
+                period,
+                start_time,
+                end_time,
+            ),
+        )?;
+
+        let resp: Response = Response::new()
+            .add_attribute("action", "create_stream")
+            .add_attribute("stream_id", id.to_string())
+            .add_attribute("beneficiary", beneficiary.as_str())
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    pub fn claim_stream(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let mut stream = STREAMS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::StreamNotFoundErr { id })?;
+
+        let claimable = stream.vested(env.block.time)? - stream.claimed;
+        // Other streams keep their reserve, this one may use its own.
+        let balance = available_balances(deps.as_ref(), &env, Some(id))?
+            .into_iter()
+            .find(|coin| coin.denom == stream.denom)
+            .map(|coin| coin.amount)
+            .unwrap_or_default();
+        let released = std::cmp::min(claimable, balance);
+
+        if released.is_zero() {
+            return Err(ContractError::NothingToClaimErr { id });
+        }
+
+        stream.claimed += released;
+        STREAMS.save(deps.storage, id, &stream)?;
+
+        let amount = vec![Coin::new(released.u128(), stream.denom)];
+
+        let resp: Response = bank_send(Response::new(), stream.beneficiary, amount)
+            .add_attribute("action", "claim_stream")
+            .add_attribute("stream_id", id.to_string())
+            .add_attribute("released", released.to_string())
+            .add_attribute("sender", info.sender.as_str());
+
+        Ok(resp)
+    }
+
+    fn register_donation(
+        deps: DepsMut,
+        env: Env,
+        mut state: State,
+        accepted: bool,
+        donor: Addr,
+        amount: Vec<Coin>,
+    ) -> Result<(Response, Uint128), ContractError> {
+        let mut resp = Response::new();
+        let mut matched = Uint128::zero();
+
+        if accepted {
+            state.counter += 1;
+
+            matched = match_donation(deps.storage, &env, &donor, &amount)?;
+            if !matched.is_zero() {
+                resp = resp.add_attribute("matched", matched.to_string());
+            }
+
+            if let Some(parent) = &mut state.donating_parent {
+                *parent -= 1;
+
+                if *parent == 0 {
+                    let parent_donation = PARENT_DONATION.load(deps.storage)?;
+                    *parent = parent_donation.donating_parent_period;
+
+                    let payouts = PARENT_PAYOUTS.may_load(deps.storage)?.unwrap_or_default();
+                    PARENT_PAYOUTS.save(deps.storage, &(payouts + 1))?;
+
+                    let msgs =
+                        parent_payout(deps.as_ref(), &env, &parent_donation).map_err(|err| {
+                            ContractError::ParentPayoutFailed {
+                                parent: parent_donation.address.to_string(),
+                                reason: err.to_string(),
+                            }
+                        })?;
+
+                    resp = resp
+                        .add_submessages(msgs)
+                        .add_attribute("donation_to_parent", parent_donation.address.to_string());
+                }
+            }
+
+            STATE.save(deps.storage, &state)?;
+
+            if let Some(receipt_contract) = RECEIPT_CONTRACT.may_load(deps.storage)? {
+                let token_id = RECEIPT_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+                RECEIPT_COUNT.save(deps.storage, &token_id)?;
+
+                let msg = WasmMsg::Execute {
+                    contract_addr: receipt_contract.to_string(),
+                    msg: to_json_binary(&ReceiptMintMsg::Mint {
+                        token_id: token_id.to_string(),
+                        owner: donor.to_string(),
+                        token_uri: None,
+                        extension: ReceiptMetadata {
+                            donor: donor.clone(),
+                            amount,
+                            counter: state.counter,
+                        },
+                    })?,
+                    funds: vec![],
+                };
+
+                resp = resp
+                    .add_message(msg)
+                    .add_attribute("receipt", token_id.to_string());
+            }
+        }
+
+        resp = resp
+            .add_attribute("action", "donate")
+            .add_attribute("counter", state.counter.to_string().as_str())
+            .add_attribute("sender", donor.as_str());
+
+        Ok((resp, matched))
+    }
+
+    fn parent_payout(
+        deps: Deps,
+        env: &Env,
+        parent_donation: &ParentDonation,
+    ) -> Result<Vec<SubMsg>, ContractError> {
+        let funds: Vec<_> = forwardable_balances(deps, env, None)?
+            .into_iter()
+            .map(|mut coin| {
+                coin.amount = coin.amount.mul_floor(parent_donation.part);
+                coin
+            })
+            .filter(|coin| !coin.amount.is_zero())
+            .collect();
+
+        if let Some(remote) = &parent_donation.remote {
+            return parent_transfers(env, &parent_donation.address, remote, funds);
+        }
+
+        let parent = CountingContractHelper(parent_donation.address.clone());
+        let msg = parent.donate(funds)?;
+
+        let cw20_coins: Vec<_> = cw20_balances(deps, &env.contract.address)?
+            .into_iter()
+            .map(|coin| Cw20Coin {
+                amount: coin.amount.mul_floor(parent_donation.part),
+                ..coin
+            })
+            .filter(|coin| !coin.amount.is_zero())
+            .collect();
+
+        // The parent rejects tokens it hasn't allowed, which would revert the whole payout, so
+        // those stay here. A parent that can't list its tokens accepts none of them.
+        let accepted: Vec<_> = if cw20_coins.is_empty() {
+            vec![]
+        } else {
+            parent
+                .query_cw20_tokens(&deps.querier)
+                .map(|resp| resp.tokens)
+                .unwrap_or_default()
+        };
+
+        let cw20_msgs = cw20_coins
+            .into_iter()
+            .filter(|coin| {
+                accepted
+                    .iter()
+                    .any(|token| token.token.as_str() == coin.address)
+            })
+            .map(|coin| parent.donate_cw20(coin.address, coin.amount))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let msgs = [vec![msg], cw20_msgs].concat();
+
+        Ok(msgs.into_iter().map(SubMsg::new).collect())
+    }
+
+    fn attribute_to_campaign(
+        deps: DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        id: u64,
+    ) -> Result<(), ContractError> {
+        let mut campaign = CAMPAIGNS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::CampaignNotFoundErr { id })?;
+
+        if campaign.status(env.block.time) != CampaignStatus::Active {
+            return Err(ContractError::CampaignNotActiveErr { id });
+        }
+
+        add_coins(&mut campaign.raised, &info.funds);
+        CAMPAIGNS.save(deps.storage, id, &campaign)?;
+
+        let mut donated = CAMPAIGN_DONATIONS
+            .may_load(deps.storage, (id, &info.sender))?
+            .unwrap_or_default();
+        add_coins(&mut donated, &info.funds);
+        CAMPAIGN_DONATIONS.save(deps.storage, (id, &info.sender), &donated)?;
+
+        Ok(())
+    }
+
+    fn add_coins(total: &mut Vec<Coin>, coins: &[Coin]) {
+        for coin in coins {
+            match total.iter_mut().find(|c| c.denom == coin.denom) {
+                Some(c) => c.amount += coin.amount,
+                None => total.push(coin.clone()),
+            }
+        }
+    }
+
+    fn sub_coins(total: &mut Vec<Coin>, coins: &[Coin]) {
+        for coin in coins {
+            if let Some(c) = total.iter_mut().find(|c| c.denom == coin.denom) {
+                c.amount = c.amount.saturating_sub(coin.amount);
+            }
+        }
+
+        total.retain(|coin| !coin.amount.is_zero());
+    }
+
+    fn match_donation(
+        storage: &mut dyn Storage,
+        env: &Env,
+        donor: &Addr,
+        amount: &[Coin],
+    ) -> Result<Uint128, ContractError> {
+        let mut matching = match MATCHING.may_load(storage)? {
+            Some(matching) if env.block.time < matching.ends_at => matching,
+            _ => return Ok(Uint128::zero()),
+        };
+
+        let donated = amount
+            .iter()
+            .filter(|coin| coin.denom == matching.denom)
+            .fold(Uint128::zero(), |total, coin| total + coin.amount);
+
+        let donor_matched = MATCHED_DONORS
+            .may_load(storage, (matching.round, donor))?
+            .unwrap_or_default();
+
+        let matched = [
+            donated.mul_floor(matching.ratio),
+            matching.per_donor_cap.saturating_sub(donor_matched),
+            matching.global_cap.saturating_sub(matching.matched),
+            matching.pool,
+        ]
+        .into_iter()
+        .min()
+        .unwrap_or_default();
+
+        if matched.is_zero() {
+            return Ok(matched);
+        }
+
+        matching.pool -= matched;
+        matching.matched += matched;
+        MATCHING.save(storage, &matching)?;
+        MATCHED_DONORS.save(storage, (matching.round, donor), &(donor_matched + matched))?;
+
+        Ok(matched)
+    }
+
+    // A later round starts from a fresh pool, so only the running round gets its match back.
+    fn unmatch_donation(storage: &mut dyn Storage, donation: &Donation) -> StdResult<()> {
+        let mut matching = match MATCHING.may_load(storage)? {
+            Some(matching) if matching.round == donation.matching_round => matching,
+            _ => return Ok(()),
+        };
+
+        matching.pool += donation.matched;
+        matching.matched = matching.matched.saturating_sub(donation.matched);
+        MATCHING.save(storage, &matching)?;
+
+        let key = (matching.round, &donation.donor);
+        let donor_matched = MATCHED_DONORS
+            .may_load(storage, key)?
+            .unwrap_or_default()
+            .saturating_sub(donation.matched);
+        MATCHED_DONORS.save(storage, key, &donor_matched)
+    }
+
+    // Donations still within their refund window are locked as well, unless a parent payout
+    // already forwarded them.
+    fn available_balances(
+        deps: Deps,
+        env: &Env,
+        claiming_stream: Option<u64>,
+    ) -> Result<Vec<Coin>, ContractError> {
+        let mut balances = forwardable_balances(deps, env, claiming_stream)?;
+
+        if let Some(grace_period) = REFUND_GRACE_PERIOD.may_load(deps.storage)? {
+            let payouts = PARENT_PAYOUTS.may_load(deps.storage)?.unwrap_or_default();
+
+            for donation in DONATIONS.range(deps.storage, None, None, Order::Descending) {
+                let (_, donation) = donation?;
+
+                if donation.payouts_before < payouts
+                    || donation.donated_at.plus_seconds(grace_period) <= env.block.time
+                {
+                    break;
+                }
+
+                for locked in &donation.funds {
+                    if let Some(coin) = balances.iter_mut().find(|c| c.denom == locked.denom) {
+                        coin.amount = coin.amount.saturating_sub(locked.amount);
+                    }
+                }
+            }
+
+            balances.retain(|coin| !coin.amount.is_zero());
+        }
+
+        Ok(balances)
+    }
+
+    // Matching pool and matched funds stay locked until the matching campaign ends, funds of
+    // refundable campaigns until they succeed, and whatever streams didn't pay out yet.
+    // 2.x deprecates querying all balances, but there is no list of denoms to query one by one.
+    #[cfg_attr(feature = "cosmwasm_2", allow(deprecated))]
+    fn forwardable_balances(
+        deps: Deps,
+        env: &Env,
+        claiming_stream: Option<u64>,
+    ) -> Result<Vec<Coin>, ContractError> {
+        let mut balances = deps
+            .querier
+            .query_all_balances(&env.contract.address)
+            .map_err(querier_failure)?;
+
+        for donation in IBC_DONATIONS.range(deps.storage, None, None, Order::Ascending) {
+            let (_, donation) = donation?;
+
+            for locked in &donation.funds {
+                if let Some(coin) = balances.iter_mut().find(|c| c.denom == locked.denom) {
+                    coin.amount = coin.amount.saturating_sub(locked.amount);
+                }
+            }
+        }
+
+        for campaign in CAMPAIGNS.range(deps.storage, None, None, Order::Ascending) {
+            let (_, campaign) = campaign?;
+
+            for locked in campaign.locked(env.block.time) {
+                if let Some(coin) = balances.iter_mut().find(|c| c.denom == locked.denom) {
+                    coin.amount = coin.amount.saturating_sub(locked.amount);
+                }
+            }
+        }
+
+        if let Some(matching) = MATCHING.may_load(deps.storage)? {
+            if env.block.time < matching.ends_at {
+                let locked = matching.pool + matching.matched;
+
+                for coin in &mut balances {
+                    if coin.denom == matching.denom {
+                        coin.amount = coin.amount.saturating_sub(locked);
+                    }
+                }
+            }
+        }
+
+        for stream in STREAMS.range(deps.storage, None, None, Order::Ascending) {
+            let (id, stream) = stream?;
+
+            if Some(id) == claiming_stream {
+                continue;
+            }
+
+            let locked = stream.unclaimed()?;
+            if let Some(coin) = balances.iter_mut().find(|c| c.denom == stream.denom) {
+                coin.amount = coin.amount.saturating_sub(locked);
+            }
+        }
+
+        balances.retain(|coin| !coin.amount.is_zero());
+
+        Ok(balances)
+    }
+
+    // Cw20 tokens can't travel over ICS-20, so remote parents only receive native funds.
+    fn parent_transfers(
+        env: &Env,
+        receiver: &Addr,
+        remote: &RemoteParent,
+        funds: Vec<Coin>,
+    ) -> Result<Vec<SubMsg>, ContractError> {
+        let timeout = env.block.time.plus_seconds(remote.timeout);
+
+        // The transfer module only reports the outcome back to the sender through an ADR-8
+        // source callback.
+        #[cfg(feature = "cosmwasm_2")]
+        let memo = to_json_string(&IbcCallbackRequest::source(IbcSrcCallback {
+            address: env.contract.address.clone(),
+            gas_limit: None,
+        }))?;
+
+        funds
+            .into_iter()
+            .map(|amount| {
+                #[cfg(feature = "cosmwasm_2")]
+                let transfer = to_json_binary(&ParentTransfer::new(
+                    remote.channel.clone(),
+                    receiver.to_string(),
+                    amount.clone(),
+                    timeout,
+                ))?;
+
+                let msg = IbcMsg::Transfer {
+                    channel_id: remote.channel.clone(),
+                    to_address: receiver.to_string(),
+                    amount,
+                    timeout: IbcTimeout::with_timestamp(timeout),
+                    #[cfg(feature = "cosmwasm_2")]
+                    memo: Some(memo.clone()),
+                };
+
+                // The packet sequence only comes back in the transfer response, so the reply
+                // starts tracking the transfer. 1.x has no IBC callbacks to ever settle it.
+                #[cfg(feature = "cosmwasm_2")]
+                let msg =
+                    SubMsg::reply_on_success(msg, PARENT_TRANSFER_REPLY_ID).with_payload(transfer);
+                #[cfg(not(feature = "cosmwasm_2"))]
+                let msg = SubMsg::new(msg);
+
+                Ok(msg)
+            })
+            .collect()
+    }
+
+    fn cw20_balances(deps: Deps, contract: &Addr) -> Result<Vec<Cw20Coin>, ContractError> {
+        let mut balances = vec![];
+
+        for token in CW20_TOKENS.keys(deps.storage, None, None, Order::Ascending) {
+            let token = token?;
+            let BalanceResponse { balance } = deps
+                .querier
+                .query_wasm_smart(
+                    &token,
+                    &Cw20QueryMsg::Balance {
+                        address: contract.to_string(),
+                    },
+                )
+                .map_err(querier_failure)?;
+
+            if !balance.is_zero() {
+                balances.push(Cw20Coin {
+                    address: token.into_string(),
+                    amount: balance,
+                });
+            }
+        }
+
+        Ok(balances)
+    }
+
+    fn querier_failure(err: StdError) -> ContractError {
+        ContractError::QuerierFailure {
+            reason: err.to_string(),
+        }
+    }
+
+    fn cw20_transfers(
+        recipient: &Addr,
+        amount: Vec<Cw20Coin>,
+    ) -> Result<Vec<WasmMsg>, ContractError> {
+        amount
+            .into_iter()
+            .map(|coin| {
+                Ok(WasmMsg::Execute {
+                    contract_addr: coin.address,
+                    msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: recipient.to_string(),
+                        amount: coin.amount,
+                    })?,
+                    funds: vec![],
+                })
+            })
+            .collect()
+    }
+
+    fn cw20_withdrawal_funds(
+        balances: Vec<Cw20Coin>,
+        requested: &[Cw20Coin],
+    ) -> Result<Vec<Cw20Coin>, ContractError> {
+        if requested.is_empty() {
+            return Ok(balances);
+        }
+
+        let unavailable: Vec<_> = requested
+            .iter()
+            .filter(|c| !c.amount.is_zero() && !balances.iter().any(|b| b.address == c.address))
+            .map(|c| c.address.clone())
+            .collect();
+
+        if !unavailable.is_empty() {
+            return Err(ContractError::UnavailableDenomsErr {
+                denoms: unavailable,
+            });
+        }
+
+        let amount = balances
+            .into_iter()
+            .filter_map(|balance| {
+                let limit = requested
+                    .iter()
+                    .find(|c| c.address == balance.address)
+                    .map(|c| c.amount)
+                    .unwrap_or_default();
+
+                let amount = std::cmp::min(balance.amount, limit);
+                (!amount.is_zero()).then_some(Cw20Coin { amount, ..balance })
+            })
+            .collect();
+
+        Ok(amount)
+    }
+
+    // Remote receivers are paid over ICS-20 on the channel their prefix is configured with.
+    fn send_native(resp: Response, env: &Env, receiver: Address, amount: Vec<Coin>) -> Response {
+        match receiver {
+            Address::Local(receiver) => bank_send(resp, receiver, amount),
+            Address::Remote { address, channel } => {
+                let timeout = env.block.time.plus_seconds(PACKET_LIFETIME);
+                let msgs = amount.into_iter().map(|amount| IbcMsg::Transfer {
+                    channel_id: channel.clone(),
+                    to_address: address.to_string(),
+                    amount,
+                    timeout: IbcTimeout::with_timestamp(timeout),
+                    #[cfg(feature = "cosmwasm_2")]
+                    memo: None,
+                });
+
+                resp.add_messages(msgs)
+            }
+        }
+    }
+
+    fn bank_send(resp: Response, to_address: impl Into<String>, amount: Vec<Coin>) -> Response {
+        if amount.is_empty() {
+            return resp;
+        }
+
+        resp.add_message(BankMsg::Send {
+            to_address: to_address.into(),
+            amount,
+        })
+    }
+
+    fn ensure_not_time_locked(deps: Deps, env: &Env) -> Result<(), ContractError> {
+        if withdrawal_config(deps.storage, env.block.time)?.delay > 0 {
+            return Err(ContractError::WithdrawalTimeLockedErr {});
+        }
+
+        Ok(())
+    }
+
+    fn withdrawal_funds(
+        mut contract_balances: Vec<Coin>,
+        funds: &[Coin],
+    ) -> Result<Vec<Coin>, ContractError> {
+        if !funds.is_empty() {
+            let unavailable: Vec<_> = funds
+                .iter()
+                .filter(|c| {
+                    !c.amount.is_zero()
+                        && !contract_balances
+                            .iter()
+                            .any(|b| b.denom == c.denom && !b.amount.is_zero())
+                })
+                .map(|c| c.denom.clone())
+                .collect();
+
+            if !unavailable.is_empty() {
+                return Err(ContractError::UnavailableDenomsErr {
+                    denoms: unavailable,
+                });
+            }
+
+            for coin in &mut contract_balances {
+                let limit = funds
+                    .iter()
+                    .find(|c| c.denom == coin.denom)
+                    .map(|c| c.amount)
+                    .unwrap_or(Uint128::zero());
+
+                coin.amount = std::cmp::min(coin.amount, limit);
+            }
+        }
+
+        contract_balances.retain(|coin| !coin.amount.is_zero());
+
+        Ok(contract_balances)
+    }
+}
+
+pub mod query {
+    use cosmwasm_std::{Deps, Env, Order, StdError, StdResult};
+    use cw2::get_contract_version;
+
+    use crate::{
+        migration,
+        msg::{
+            AllowedPrefix, AllowedPrefixesResp, CampaignDonationResp, CampaignResp, CampaignsResp,
+            Cw20TokenResp, Cw20TokensResp, DonationResp, IbcChannelsResp, IbcDonationResp,
+            MatchedDonationResp, MatchingResp, MigrationPreviewResp, MigrationStepResp,
+            PendingWithdrawalDelayResp, PendingWithdrawalResp, PendingWithdrawalsResp,
+            ReceiptContractResp, StreamResp, StreamsResp, ValueResp, WithdrawalDelayResp,
+        },
+        state::{
+            Campaign, PendingWithdrawal, Stream, ADDRESS_PREFIXES, CAMPAIGNS, CAMPAIGN_DONATIONS,
+            CW20_TOKENS, DONATIONS, IBC_CHANNELS, IBC_DONATIONS, MATCHED_DONORS, MATCHING,
+            PENDING_WITHDRAWALS, PENDING_WITHDRAWAL_CONFIG, RECEIPT_CONTRACT, REFUND_GRACE_PERIOD,
+            STATE, STREAMS,
+        },
+    };
+    #[cfg(feature = "cosmwasm_2")]
+    use crate::{
+        msg::{ParentTransferResp, ParentTransfersResp},
+        state::PARENT_TRANSFERS,
+    };
+
+    use super::exec::withdrawal_config;
+
+    pub fn migration_preview(deps: Deps) -> StdResult<MigrationPreviewResp> {
+        let stored_version = get_contract_version(deps.storage)?.version;
+
+        let plan = migration::parse_version(&stored_version)
+            .and_then(|from| {
+                let to = migration::parse_version(super::CONTRACT_VERSION)?;
+                migration::plan(deps.storage, from, &to)
+            })
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+        let steps = plan
+            .steps
+            .into_iter()
+            .map(|step| MigrationStepResp {
+                from: step.from.into(),
+                to: step.to.into(),
+            })
+            .collect();
+
+        Ok(MigrationPreviewResp {
+            stored_version,
+            target_version: super::CONTRACT_VERSION.into(),
+            steps,
+            stored_state: plan.before,
+        })
+    }
+
+    #[cfg(any(test, feature = "tests", feature = "tests_cosmwasm_2"))]
+    pub fn storage_keys(deps: Deps) -> StdResult<crate::msg::StorageKeysResp> {
+        let keys = deps
+            .storage
+            .range(None, None, Order::Ascending)
+            .map(|(key, _)| String::from_utf8_lossy(&key).into_owned())
+            .collect();
+
+        Ok(crate::msg::StorageKeysResp { keys })
+    }
+
+    pub fn allowed_prefixes(deps: Deps) -> StdResult<AllowedPrefixesResp> {
+        let prefixes = ADDRESS_PREFIXES
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|prefix| {
+                let (prefix, config) = prefix?;
+
+                Ok(AllowedPrefix {
+                    prefix,
+                    lengths: config.lengths,
+                    channel: config.channel,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(AllowedPrefixesResp { prefixes })
+    }
+
+    pub fn ibc_channels(deps: Deps) -> StdResult<IbcChannelsResp> {
+        let channels = IBC_CHANNELS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+
+        Ok(IbcChannelsResp { channels })
+    }
+
+    #[cfg(feature = "cosmwasm_2")]
+    pub fn parent_transfers(deps: Deps) -> StdResult<ParentTransfersResp> {
+        let transfers = PARENT_TRANSFERS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|transfer| {
+                let ((channel, sequence), transfer) = transfer?;
+
+                Ok(ParentTransferResp {
+                    channel,
+                    sequence,
+                    receiver: transfer.receiver,
+                    amount: transfer.amount,
+                    timeout: transfer.timeout,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(ParentTransfersResp { transfers })
+    }
+
+    pub fn ibc_donation(deps: Deps, id: u64) -> StdResult<IbcDonationResp> {
+        let donation = IBC_DONATIONS.load(deps.storage, id)?;
+
+        Ok(IbcDonationResp {
+            id,
+            donor: donation.donor,
+            channel: donation.channel,
+            funds: donation.funds,
+        })
+    }
+
+    pub fn value(deps: Deps) -> StdResult<ValueResp> {
+        let value = STATE.load(deps.storage)?.counter;
+        Ok(ValueResp { value })
+    }
+
+    pub fn pending_withdrawal(deps: Deps, id: u64) -> StdResult<PendingWithdrawalResp> {
+        let withdrawal = PENDING_WITHDRAWALS.load(deps.storage, id)?;
+        Ok(pending_withdrawal_resp(id, withdrawal))
+    }
+
+    pub fn pending_withdrawals(deps: Deps) -> StdResult<PendingWithdrawalsResp> {
+        let withdrawals = PENDING_WITHDRAWALS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(id, withdrawal)| pending_withdrawal_resp(id, withdrawal)))
+            .collect::<StdResult<_>>()?;
+
+        Ok(PendingWithdrawalsResp { withdrawals })
+    }
+
+    pub fn withdrawal_delay(deps: Deps, env: Env) -> StdResult<WithdrawalDelayResp> {
+        let config = withdrawal_config(deps.storage, env.block.time)?;
+        let pending = PENDING_WITHDRAWAL_CONFIG
+            .may_load(deps.storage)?
+            .filter(|pending| env.block.time < pending.effective_at)
+            .map(|pending| PendingWithdrawalDelayResp {
+                delay: pending.config.delay,
+                guardian: pending.config.guardian,
+                effective_at: pending.effective_at,
+            });
+
+        Ok(WithdrawalDelayResp {
+            delay: config.delay,
+            guardian: config.guardian,
+            pending,
+        })
+    }
+
+    pub fn cw20_tokens(deps: Deps) -> StdResult<Cw20TokensResp> {
+        let tokens = CW20_TOKENS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                item.map(|(token, minimal_donation)| Cw20TokenResp {
+                    token,
+                    minimal_donation,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(Cw20TokensResp { tokens })
+    }
+
+    pub fn donation(deps: Deps, id: u64) -> StdResult<DonationResp> {
+        let donation = DONATIONS.load(deps.storage, id)?;
+        let grace_period = REFUND_GRACE_PERIOD
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+
+        Ok(DonationResp {
+            id,
+            donor: donation.donor,
+            funds: donation.funds,
+            refundable_until: donation.donated_at.plus_seconds(grace_period),
+        })
+    }
+
+    pub fn campaign(deps: Deps, env: Env, id: u64) -> StdResult<CampaignResp> {
+        let campaign = CAMPAIGNS.load(deps.storage, id)?;
+        Ok(campaign_resp(&env, id, campaign))
+    }
+
+    pub fn campaigns(deps: Deps, env: Env) -> StdResult<CampaignsResp> {
+        let campaigns = CAMPAIGNS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(id, campaign)| campaign_resp(&env, id, campaign)))
+            .collect::<StdResult<_>>()?;
+
+        Ok(CampaignsResp { campaigns })
+    }
+
+    pub fn campaign_donation(
+        deps: Deps,
+        campaign: u64,
+        donor: String,
+    ) -> StdResult<CampaignDonationResp> {
+        let donor = deps.api.addr_validate(&donor)?;
+        let donated = CAMPAIGN_DONATIONS
+            .may_load(deps.storage, (campaign, &donor))?
+            .unwrap_or_default();
+
+        Ok(CampaignDonationResp { donated })
+    }
+
+    fn campaign_resp(env: &Env, id: u64, campaign: Campaign) -> CampaignResp {
+        CampaignResp {
+            id,
+            status: campaign.status(env.block.time),
+            targets: campaign.targets,
+            start_time: campaign.start_time,
+            end_time: campaign.end_time,
+            refundable: campaign.refundable,
+            raised: campaign.raised,
+            refunded: campaign.refunded,
+        }
+    }
+
+    pub fn matching(deps: Deps) -> StdResult<MatchingResp> {
+        let matching = MATCHING.load(deps.storage)?;
+
+        Ok(MatchingResp {
+            denom: matching.denom,
+            ratio: matching.ratio,
+            per_donor_cap: matching.per_donor_cap,
+            global_cap: matching.global_cap,
+            ends_at: matching.ends_at,
+            pool_remaining: matching.pool,
+            matched_total: matching.matched,
+        })
+    }
+
+    pub fn matched_donation(deps: Deps, donor: String) -> StdResult<MatchedDonationResp> {
+        let donor = deps.api.addr_validate(&donor)?;
+        let round = MATCHING.load(deps.storage)?.round;
+        let matched = MATCHED_DONORS
+            .may_load(deps.storage, (round, &donor))?
+            .unwrap_or_default();
+
+        Ok(MatchedDonationResp { matched })
+    }
+
+    pub fn receipt_contract(deps: Deps) -> StdResult<ReceiptContractResp> {
+        let contract = RECEIPT_CONTRACT.may_load(deps.storage)?;
+        Ok(ReceiptContractResp { contract })
+    }
+
+    pub fn stream(deps: Deps, env: Env, id: u64) -> StdResult<StreamResp> {
+        let stream = STREAMS.load(deps.storage, id)?;
+        stream_resp(&env, id, stream)
+    }
+
+    pub fn streams(deps: Deps, env: Env) -> StdResult<StreamsResp> {
+        let streams = STREAMS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.and_then(|(id, stream)| stream_resp(&env, id, stream)))
+            .collect::<StdResult<_>>()?;
+
+        Ok(StreamsResp { streams })
+    }
+
+    fn stream_resp(env: &Env, id: u64, stream: Stream) -> StdResult<StreamResp> {
+        Ok(StreamResp {
+            id,
+            vested: stream.vested(env.block.time)?,
+            beneficiary: stream.beneficiary,
+            denom: stream.denom,
+            amount_per_period: stream.amount_per_period,
+            period: stream.period,
+            start_time: stream.start_time,
+            end_time: stream.end_time,
+            claimed: stream.claimed,
+        })
+    }
+
+    fn pending_withdrawal_resp(id: u64, withdrawal: PendingWithdrawal) -> PendingWithdrawalResp {
+        PendingWithdrawalResp {
+            id,
+            receiver: withdrawal.receiver,
+            funds: withdrawal.funds,
+            cw20_funds: withdrawal.cw20_funds,
+            release_at: withdrawal.release_at,
+        }
+    }
+}
+
+pub mod ibc {
+    use cosmwasm_std::{
+        from_json, to_json_binary, BankMsg, Binary, DepsMut, IbcBasicResponse, IbcChannel,
+        IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcChannelOpenResponse,
+        IbcOrder, IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse,
+    };
+    #[cfg(feature = "cosmwasm_2")]
+    use cosmwasm_std::{IbcPacket, IbcSourceCallbackMsg, Reply, Response, StdError};
+
+    #[cfg(feature = "cosmwasm_2")]
+    use crate::state::{ParentTransfer, PARENT_TRANSFERS};
+    use crate::{
+        error::ContractError,
+        msg::{IbcAck, IbcPacketMsg},
+        state::{IbcDonation, IBC_CHANNELS, IBC_DONATIONS, STATE},
+    };
+
+    pub const IBC_VERSION: &str = "counting-1";
+    pub const PACKET_LIFETIME: u64 = 60 * 60;
+    #[cfg(feature = "cosmwasm_2")]
+    pub const PARENT_TRANSFER_REPLY_ID: u64 = 1;
+
+    #[cfg(not(feature = "cosmwasm_2"))]
+    pub fn channel_open(msg: IbcChannelOpenMsg) -> Result<IbcChannelOpenResponse, ContractError> {
+        validate_channel(msg.channel(), msg.counterparty_version())
+    }
+
+    // No version in the response accepts the channel with the one it was proposed with.
+    #[cfg(feature = "cosmwasm_2")]
+    pub fn channel_open(msg: IbcChannelOpenMsg) -> Result<IbcChannelOpenResponse, ContractError> {
+        validate_channel(msg.channel(), msg.counterparty_version())?;
+
+        Ok(None)
+    }
+
+    pub fn channel_connect(
+        deps: DepsMut,
+        msg: IbcChannelConnectMsg,
+    ) -> Result<IbcBasicResponse, ContractError> {
+        let channel = msg.channel();
+        validate_channel(channel, msg.counterparty_version())?;
+
+        IBC_CHANNELS.save(
+            deps.storage,
+            &channel.endpoint.channel_id,
+            &channel.counterparty_endpoint,
+        )?;
+
+        let resp = IbcBasicResponse::new()
+            .add_attribute("action", "ibc_channel_connect")
+            .add_attribute("channel", &channel.endpoint.channel_id);
+
+        Ok(resp)
+    }
+
+    pub fn channel_close(
+        deps: DepsMut,
+        msg: IbcChannelCloseMsg,
+    ) -> Result<IbcBasicResponse, ContractError> {
+        let channel = &msg.channel().endpoint.channel_id;
+        IBC_CHANNELS.remove(deps.storage, channel);
+
+        let resp = IbcBasicResponse::new()
+            .add_attribute("action", "ibc_channel_close")
+            .add_attribute("channel", channel);
+
+        Ok(resp)
+    }
+
+    pub fn packet_receive(
+        deps: DepsMut,
+        msg: IbcPacketReceiveMsg,
+    ) -> Result<IbcReceiveResponse, ContractError> {
+        let channel = msg.packet.dest.channel_id;
+
+        match receive_donation(deps, &channel, &msg.packet.data) {
+            Ok(resp) => Ok(resp),
+            Err(err) => {
+                let resp = receive_response(to_json_binary(&IbcAck::Error(err.to_string()))?)
+                    .add_attribute("action", "ibc_donate")
+                    .add_attribute("channel", channel)
+                    .add_attribute("error", err.to_string());
+
+                Ok(resp)
+            }
+        }
+    }
+
+    pub fn packet_ack(
+        deps: DepsMut,
+        msg: IbcPacketAckMsg,
+    ) -> Result<IbcBasicResponse, ContractError> {
+        let IbcPacketMsg::Donate { id, .. } = from_json(&msg.original_packet.data)?;
+        let ack = from_json(&msg.acknowledgement.data)?;
+        let donation = take_donation(deps, id)?;
+
+        let resp = IbcBasicResponse::new()
+            .add_attribute("action", "ibc_donate_ack")
+            .add_attribute("ibc_donation_id", id.to_string());
+
+        match ack {
+            IbcAck::Result(_) => Ok(resp.add_attribute("success", "true")),
+            IbcAck::Error(err) => Ok(refund(resp, donation)
+                .add_attribute("success", "false")
+                .add_attribute("error", err)),
+        }
+    }
+
+    pub fn packet_timeout(
+        deps: DepsMut,
+        msg: IbcPacketTimeoutMsg,
+    ) -> Result<IbcBasicResponse, ContractError> {
+        let IbcPacketMsg::Donate { id, .. } = from_json(&msg.packet.data)?;
+        let donation = take_donation(deps, id)?;
+
+        let resp = IbcBasicResponse::new()
+            .add_attribute("action", "ibc_donate_timeout")
+            .add_attribute("ibc_donation_id", id.to_string());
+
+        Ok(refund(resp, donation))
+    }
+
+    #[cfg(feature = "cosmwasm_2")]
+    pub fn source_callback(
+        deps: DepsMut,
+        msg: IbcSourceCallbackMsg,
+    ) -> Result<IbcBasicResponse, ContractError> {
+        match msg {
+            IbcSourceCallbackMsg::Acknowledgement(msg) => {
+                let ack = from_json(&msg.acknowledgement.data)?;
+                parent_transfer_ack(deps, &msg.original_packet, Some(ack))
+            }
+            IbcSourceCallbackMsg::Timeout(msg) => parent_transfer_ack(deps, &msg.packet, None),
+        }
+    }
+
+    // Parent payouts are tracked by their packet, which only the transfer response names. Chains
+    // on CosmWasm 2 return it in msg_responses, older ones in the deprecated data field.
+    #[cfg(feature = "cosmwasm_2")]
+    #[allow(deprecated)]
+    pub fn transfer_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+        if msg.id != PARENT_TRANSFER_REPLY_ID {
+            return Err(StdError::generic_err(format!("Unknown reply id {}", msg.id)).into());
+        }
+
+        let transfer: ParentTransfer = from_json(&msg.payload)?;
+        let resp = msg.result.into_result().map_err(StdError::generic_err)?;
+        let sequence = resp
+            .msg_responses
+            .first()
+            .map(|resp| &resp.value)
+            .or(resp.data.as_ref())
+            .and_then(|data| transfer_sequence(data))
+            .ok_or_else(|| StdError::parse_err("MsgTransferResponse", "missing sequence"))?;
+
+        PARENT_TRANSFERS.save(deps.storage, (&transfer.channel, sequence), &transfer)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "parent_transfer")
+            .add_attribute("channel", transfer.channel)
+            .add_attribute("sequence", sequence.to_string());
+
+        Ok(resp)
+    }
+
+    // MsgTransferResponse only holds the sequence, a varint in field 1.
+    #[cfg(feature = "cosmwasm_2")]
+    fn transfer_sequence(data: &[u8]) -> Option<u64> {
+        let (&tag, mut rest) = data.split_first()?;
+        if tag != 0x08 {
+            return None;
+        }
+
+        let mut sequence = 0u64;
+        for shift in (0..64).step_by(7) {
+            let (&byte, tail) = rest.split_first()?;
+            rest = tail;
+            sequence |= u64::from(byte & 0x7f) << shift;
+
+            if byte & 0x80 == 0 {
+                return Some(sequence);
+            }
+        }
+
+        None
+    }
+
+    // On failure the transfer module returns the funds to this contract on its own, they only
+    // have to be released from tracking.
+    #[cfg(feature = "cosmwasm_2")]
+    fn parent_transfer_ack(
+        deps: DepsMut,
+        packet: &IbcPacket,
+        ack: Option<IbcAck>,
+    ) -> Result<IbcBasicResponse, ContractError> {
+        let channel = &packet.src.channel_id;
+        let key = (channel.as_str(), packet.sequence);
+
+        if !PARENT_TRANSFERS.has(deps.storage, key) {
+            return Err(ContractError::ParentTransferNotFoundErr {
+                channel: channel.clone(),
+                sequence: packet.sequence,
+            });
+        }
+        PARENT_TRANSFERS.remove(deps.storage, key);
+
+        let resp = IbcBasicResponse::new()
+            .add_attribute("action", "parent_transfer_ack")
+            .add_attribute("channel", channel)
+            .add_attribute("sequence", packet.sequence.to_string());
+
+        let resp = match ack {
+            Some(IbcAck::Result(_)) => resp.add_attribute("success", "true"),
+            Some(IbcAck::Error(err)) => resp
+                .add_attribute("success", "false")
+                .add_attribute("error", err),
+            None => resp
+                .add_attribute("success", "false")
+                .add_attribute("error", "timeout"),
+        };
+
+        Ok(resp)
+    }
+
+    fn validate_channel(
+        channel: &IbcChannel,
+        counterparty_version: Option<&str>,
+    ) -> Result<(), ContractError> {
+        if channel.order != IbcOrder::Unordered {
+            return Err(ContractError::InvalidIbcOrderErr {});
+        }
+
+        if channel.version != IBC_VERSION {
+            return Err(ContractError::InvalidIbcVersionErr {
+                version: channel.version.clone(),
+            });
+        }
+
+        if let Some(version) = counterparty_version {
+            if version != IBC_VERSION {
+                return Err(ContractError::InvalidIbcVersionErr {
+                    version: version.into(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn receive_donation(
+        deps: DepsMut,
+        channel: &str,
+        data: &Binary,
+    ) -> Result<IbcReceiveResponse, ContractError> {
+        let IbcPacketMsg::Donate { id, donor, .. } = from_json(data)?;
+
+        let mut state = STATE.load(deps.storage)?;
+
+        // The packet only declares the funds, they stay escrowed on the donor's chain, so they
+        // can't be trusted to meet the minimal donation. The error ack refunds the donor.
+        if !state.minimal_donation.amount.is_zero() {
+            return Err(ContractError::UnverifiedRemoteDonationErr {
+                minimal: state.minimal_donation,
+            });
+        }
+
+        state.counter += 1;
+        STATE.save(deps.storage, &state)?;
+
+        let ack = IbcAck::Result(to_json_binary(&state.counter)?);
+        let resp = receive_response(to_json_binary(&ack)?)
+            .add_attribute("action", "ibc_donate")
+            .add_attribute("channel", channel)
+            .add_attribute("ibc_donation_id", id.to_string())
+            .add_attribute("counter", state.counter.to_string())
+            .add_attribute("sender", donor);
+
+        Ok(resp)
+    }
+
+    #[cfg(not(feature = "cosmwasm_2"))]
+    fn receive_response(ack: Binary) -> IbcReceiveResponse {
+        IbcReceiveResponse::new().set_ack(ack)
+    }
+
+    #[cfg(feature = "cosmwasm_2")]
+    fn receive_response(ack: Binary) -> IbcReceiveResponse {
+        IbcReceiveResponse::new(ack)
+    }
+
+    fn take_donation(deps: DepsMut, id: u64) -> Result<IbcDonation, ContractError> {
+        let donation = IBC_DONATIONS
+            .may_load(deps.storage, id)?
+            .ok_or(ContractError::IbcDonationNotFoundErr { id })?;
+        IBC_DONATIONS.remove(deps.storage, id);
+
+        Ok(donation)
+    }
+
+    fn refund(resp: IbcBasicResponse, donation: IbcDonation) -> IbcBasicResponse {
+        if donation.funds.is_empty() {
+            return resp;
+        }
+
+        resp.add_message(BankMsg::Send {
+            to_address: donation.donor.into_string(),
+            amount: donation.funds,
+        })
     }
 }
@@ -1,16 +1,164 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Coin, Decimal, StdError, Timestamp};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
-    #[error("{0}")]
-    StdErr(#[from] StdError),
-    #[error("Unauthorized -- Only {owner} can do.")]
+    #[error("[{}] {0}", self.code())]
+    StdErr(StdError),
+    #[error("[{}] {item} not found", self.code())]
+    NotFound { item: String },
+    #[error("[{}] Querier failed: {reason}", self.code())]
+    QuerierFailure { reason: String },
+    #[error("[{}] Unauthorized -- Only {owner} can do.", self.code())]
     UnauthorizedErr { owner: String },
-    #[error("Invalid address {address}")]
+    #[error("[{}] Invalid address {address}", self.code())]
     InvalidAddressErr { address: String },
-    #[error("Invalid contract to migrate from: {contract}")]
-    InvalidName { contract: String },
-    #[error("Unsupported contract version for migration: {version}")]
-    InvalidVersion { version: String },
+    #[error("[{}] Address prefix {prefix} is not allowed", self.code())]
+    UnsupportedPrefixErr { prefix: String },
+    #[error("[{}] Invalid bech32 address {address}: {reason}", self.code())]
+    InvalidBech32Err { address: String, reason: String },
+    #[error("[{}] Invalid length {length} of address {address}", self.code())]
+    InvalidAddressLengthErr { address: String, length: u32 },
+    #[error("[{}] Cw20 tokens can't be sent to remote address {address}", self.code())]
+    RemoteCw20Err { address: String },
+    #[error("[{}] Requested denoms are not available in the contract: {denoms:?}", self.code())]
+    UnavailableDenomsErr { denoms: Vec<String> },
+    #[error("[{}] Unsupported cw20 token {token}", self.code())]
+    UnsupportedCw20Err { token: String },
+    #[error("[{}] Only unordered IBC channels are supported", self.code())]
+    InvalidIbcOrderErr {},
+    #[error("[{}] Invalid IBC channel version: {version}", self.code())]
+    InvalidIbcVersionErr { version: String },
+    #[error("[{}] Unknown IBC channel: {channel}", self.code())]
+    UnknownChannelErr { channel: String },
+    #[error("[{}] IBC donation {id} not found", self.code())]
+    IbcDonationNotFoundErr { id: u64 },
+    #[error("[{}] No in-flight parent transfer {sequence} on {channel}", self.code())]
+    ParentTransferNotFoundErr { channel: String, sequence: u64 },
+    #[error("[{}] Remote donations can't prove the minimal donation of {minimal}", self.code())]
+    UnverifiedRemoteDonationErr { minimal: Coin },
+    #[error("[{}] Donation {id} not found", self.code())]
+    DonationNotFoundErr { id: u64 },
+    #[error("[{}] Refund window for donation {id} has closed", self.code())]
+    RefundWindowClosedErr { id: u64 },
+    #[error("[{}] Donation {id} was already forwarded to the parent", self.code())]
+    DonationForwardedErr { id: u64 },
+    #[error("[{}] Payout to parent {parent} failed: {reason}", self.code())]
+    ParentPayoutFailed { parent: String, reason: String },
+    #[error("[{}] Invalid campaign: {reason}", self.code())]
+    InvalidCampaignErr { reason: String },
+    #[error("[{}] Campaign {id} not found", self.code())]
+    CampaignNotFoundErr { id: u64 },
+    #[error("[{}] Campaign {id} is not accepting donations", self.code())]
+    CampaignNotActiveErr { id: u64 },
+    #[error("[{}] Campaign {id} does not offer refunds", self.code())]
+    CampaignNotRefundableErr { id: u64 },
+    #[error("[{}] Campaign {id} has not failed", self.code())]
+    CampaignNotFailedErr { id: u64 },
+    #[error("[{}] Nothing to refund from campaign {id}", self.code())]
+    NothingToRefundErr { id: u64 },
+    #[error("[{}] Campaign {id} has ended", self.code())]
+    CampaignEndedErr { id: u64 },
+    #[error("[{}] No matching campaign configured", self.code())]
+    NoMatchingErr {},
+    #[error("[{}] Matching campaign is still active", self.code())]
+    MatchingActiveErr {},
+    #[error("[{}] Matching campaign has ended", self.code())]
+    MatchingEndedErr {},
+    #[error("[{}] Matching pool only accepts {denom}", self.code())]
+    InvalidMatchingFundsErr { denom: String },
+    #[error("[{}] Recipient shares must sum to more than zero, got {total}", self.code())]
+    InvalidSharesErr { total: Decimal },
+    #[error("[{}] Withdrawals are time-locked, use RequestWithdrawal instead", self.code())]
+    WithdrawalTimeLockedErr {},
+    #[error("[{}] Pending withdrawal {id} not found", self.code())]
+    WithdrawalNotFoundErr { id: u64 },
+    #[error("[{}] Pending withdrawal {id} is not releasable before {release_at}", self.code())]
+    WithdrawalNotMaturedErr { id: u64, release_at: Timestamp },
+    #[error("[{}] Pending withdrawal {id} has matured and can no longer be cancelled", self.code())]
+    WithdrawalMaturedErr { id: u64 },
+    #[error("[{}] No pending withdrawal delay change", self.code())]
+    NoPendingWithdrawalDelayErr {},
+    #[error("[{}] Invalid stream: {reason}", self.code())]
+    InvalidStreamErr { reason: String },
+    #[error("[{}] Stream {id} not found", self.code())]
+    StreamNotFoundErr { id: u64 },
+    #[error("[{}] Nothing to claim from stream {id}", self.code())]
+    NothingToClaimErr { id: u64 },
+    #[error("[{}] Cannot migrate from contract {found}, expected {expected}", self.code())]
+    InvalidName { expected: String, found: String },
+    #[error("[{}] Cannot migrate from version {found} to {expected}", self.code())]
+    InvalidVersion { expected: String, found: String },
+    #[error("[{}] Cannot downgrade contract from {from} to {to}", self.code())]
+    DowngradeErr { from: String, to: String },
+    #[error("[{}] Migration invariant violated: {reason}", self.code())]
+    MigrationInvariantErr { reason: String },
+    #[error("[{}] Migration override rejected: {reason}", self.code())]
+    MigrationOverrideErr { reason: String },
+}
+
+impl ContractError {
+    // Stable codes, prefixed to every message so clients can branch on them.
+    pub fn code(&self) -> u16 {
+        use ContractError::*;
+
+        match self {
+            StdErr(_) => 1000,
+            NotFound { .. } => 1001,
+            QuerierFailure { .. } => 1002,
+            UnauthorizedErr { .. } => 1003,
+            InvalidAddressErr { .. } => 1100,
+            UnsupportedPrefixErr { .. } => 1101,
+            InvalidBech32Err { .. } => 1102,
+            InvalidAddressLengthErr { .. } => 1103,
+            RemoteCw20Err { .. } => 1104,
+            UnavailableDenomsErr { .. } => 1200,
+            UnsupportedCw20Err { .. } => 1201,
+            InvalidIbcOrderErr { .. } => 1300,
+            InvalidIbcVersionErr { .. } => 1301,
+            UnknownChannelErr { .. } => 1302,
+            IbcDonationNotFoundErr { .. } => 1303,
+            ParentTransferNotFoundErr { .. } => 1304,
+            UnverifiedRemoteDonationErr { .. } => 1305,
+            DonationNotFoundErr { .. } => 1400,
+            RefundWindowClosedErr { .. } => 1401,
+            DonationForwardedErr { .. } => 1402,
+            ParentPayoutFailed { .. } => 1403,
+            InvalidCampaignErr { .. } => 1500,
+            CampaignNotFoundErr { .. } => 1501,
+            CampaignNotActiveErr { .. } => 1502,
+            CampaignNotRefundableErr { .. } => 1503,
+            CampaignNotFailedErr { .. } => 1504,
+            NothingToRefundErr { .. } => 1505,
+            CampaignEndedErr { .. } => 1506,
+            NoMatchingErr { .. } => 1600,
+            MatchingActiveErr { .. } => 1601,
+            MatchingEndedErr { .. } => 1602,
+            InvalidMatchingFundsErr { .. } => 1603,
+            InvalidSharesErr { .. } => 1700,
+            WithdrawalTimeLockedErr { .. } => 1701,
+            WithdrawalNotFoundErr { .. } => 1702,
+            WithdrawalNotMaturedErr { .. } => 1703,
+            WithdrawalMaturedErr { .. } => 1704,
+            NoPendingWithdrawalDelayErr { .. } => 1705,
+            InvalidStreamErr { .. } => 1800,
+            StreamNotFoundErr { .. } => 1801,
+            NothingToClaimErr { .. } => 1802,
+            InvalidName { .. } => 1900,
+            InvalidVersion { .. } => 1901,
+            DowngradeErr { .. } => 1902,
+            MigrationInvariantErr { .. } => 1903,
+            MigrationOverrideErr { .. } => 1904,
+        }
+    }
+}
+
+// Missing storage entries get their own variant instead of being buried in a generic error.
+impl From<StdError> for ContractError {
+    fn from(err: StdError) -> Self {
+        match err {
+            StdError::NotFound { kind, .. } => Self::NotFound { item: kind },
+            err => Self::StdErr(err),
+        }
+    }
 }
@@ -0,0 +1,411 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Coin, CosmosMsg, CustomQuery, Decimal, QuerierWrapper, StdResult,
+    Timestamp, Uint128, WasmMsg,
+};
+use cw20::{Cw20Coin, Cw20ExecuteMsg};
+use serde::de::DeserializeOwned;
+
+#[cfg(feature = "cosmwasm_2")]
+use crate::msg::ParentTransfersResp;
+use crate::msg::{
+    AllowedPrefix, AllowedPrefixesResp, CampaignDonationResp, CampaignResp, CampaignsResp,
+    Cw20TokensResp, DonationResp, ExecMsg, IbcChannelsResp, IbcDonationResp, MatchedDonationResp,
+    MatchingResp, MigrationPreviewResp, PendingWithdrawalResp, PendingWithdrawalsResp, QueryMsg,
+    ReceiptContractResp, ReceiveMsg, StreamResp, StreamsResp, ValueResp, WithdrawalDelayResp,
+};
+
+// Builds messages to and queries against a deployed counting contract, for use by other contracts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CountingContractHelper(pub Addr);
+
+impl CountingContractHelper {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn call(&self, msg: ExecMsg, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.0.to_string(),
+            msg: to_json_binary(&msg)?,
+            funds,
+        }
+        .into())
+    }
+
+    pub fn increment(&self, value: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecMsg::Increment { value }, vec![])
+    }
+
+    pub fn reset(&self, value: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecMsg::Reset { value }, vec![])
+    }
+
+    pub fn donate(&self, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        self.donate_to_campaign(None, funds)
+    }
+
+    pub fn donate_to_campaign(
+        &self,
+        campaign: impl Into<Option<u64>>,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        let campaign = campaign.into();
+        self.call(ExecMsg::Donate { campaign }, funds)
+    }
+
+    // Cw20 donations reach the contract as `ExecMsg::Receive`, sent by the token itself.
+    pub fn donate_cw20(&self, token: impl Into<String>, amount: Uint128) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: token.into(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Send {
+                contract: self.0.to_string(),
+                amount,
+                msg: to_json_binary(&ReceiveMsg::Donate {})?,
+            })?,
+            funds: vec![],
+        }
+        .into())
+    }
+
+    pub fn allow_cw20(
+        &self,
+        token: impl Into<String>,
+        minimal_donation: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecMsg::AllowCw20 {
+                token: token.into(),
+                minimal_donation,
+            },
+            vec![],
+        )
+    }
+
+    pub fn set_receipt_contract(
+        &self,
+        contract: impl Into<Option<String>>,
+    ) -> StdResult<CosmosMsg> {
+        let contract = contract.into();
+        self.call(ExecMsg::SetReceiptContract { contract }, vec![])
+    }
+
+    pub fn set_allowed_prefixes(&self, prefixes: Vec<AllowedPrefix>) -> StdResult<CosmosMsg> {
+        self.call(ExecMsg::SetAllowedPrefixes { prefixes }, vec![])
+    }
+
+    pub fn configure_matching(
+        &self,
+        denom: &str,
+        ratio: Decimal,
+        per_donor_cap: Uint128,
+        global_cap: Uint128,
+        ends_at: Timestamp,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecMsg::ConfigureMatching {
+                denom: denom.into(),
+                ratio,
+                per_donor_cap,
+                global_cap,
+                ends_at,
+            },
+            vec![],
+        )
+    }
+
+    pub fn fund_matching_pool(&self, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        self.call(ExecMsg::FundMatchingPool {}, funds)
+    }
+
+    pub fn create_campaign(
+        &self,
+        targets: Vec<Coin>,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        refundable: bool,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecMsg::CreateCampaign {
+                targets,
+                start_time,
+                end_time,
+                refundable,
+            },
+            vec![],
+        )
+    }
+
+    pub fn claim_refund(&self, campaign: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecMsg::ClaimRefund { campaign }, vec![])
+    }
+
+    pub fn set_refund_grace_period(&self, period: impl Into<Option<u64>>) -> StdResult<CosmosMsg> {
+        let period = period.into();
+        self.call(ExecMsg::SetRefundGracePeriod { period }, vec![])
+    }
+
+    pub fn refund_donation(&self, id: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecMsg::RefundDonation { id }, vec![])
+    }
+
+    pub fn withdraw(&self) -> StdResult<CosmosMsg> {
+        self.call(ExecMsg::Withdraw {}, vec![])
+    }
+
+    pub fn withdraw_to(
+        &self,
+        receiver: impl Into<String>,
+        funds: Vec<Coin>,
+        cw20_funds: Vec<Cw20Coin>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecMsg::WithdrawTo {
+                receiver: receiver.into(),
+                funds,
+                cw20_funds,
+            },
+            vec![],
+        )
+    }
+
+    pub fn withdraw_split(
+        &self,
+        recipients: Vec<(String, Decimal)>,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(ExecMsg::WithdrawSplit { recipients, funds }, vec![])
+    }
+
+    pub fn request_withdrawal(
+        &self,
+        receiver: impl Into<String>,
+        funds: Vec<Coin>,
+        cw20_funds: Vec<Cw20Coin>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecMsg::RequestWithdrawal {
+                receiver: receiver.into(),
+                funds,
+                cw20_funds,
+            },
+            vec![],
+        )
+    }
+
+    pub fn cancel_withdrawal(&self, id: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecMsg::CancelWithdrawal { id }, vec![])
+    }
+
+    pub fn execute_withdrawal(&self, id: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecMsg::ExecuteWithdrawal { id }, vec![])
+    }
+
+    pub fn set_withdrawal_delay(
+        &self,
+        delay: u64,
+        guardian: impl Into<Option<String>>,
+    ) -> StdResult<CosmosMsg> {
+        let guardian = guardian.into();
+        self.call(ExecMsg::SetWithdrawalDelay { delay, guardian }, vec![])
+    }
+
+    pub fn cancel_withdrawal_delay_change(&self) -> StdResult<CosmosMsg> {
+        self.call(ExecMsg::CancelWithdrawalDelayChange {}, vec![])
+    }
+
+    pub fn create_stream(
+        &self,
+        beneficiary: impl Into<String>,
+        amount_per_period: Coin,
+        period: u64,
+        start_time: Timestamp,
+        end_time: Timestamp,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecMsg::CreateStream {
+                beneficiary: beneficiary.into(),
+                denom: amount_per_period.denom,
+                amount_per_period: amount_per_period.amount,
+                period,
+                start_time,
+                end_time,
+            },
+            vec![],
+        )
+    }
+
+    pub fn claim_stream(&self, id: u64) -> StdResult<CosmosMsg> {
+        self.call(ExecMsg::ClaimStream { id }, vec![])
+    }
+
+    pub fn donate_remote(
+        &self,
+        channel: impl Into<String>,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecMsg::DonateRemote {
+                channel: channel.into(),
+            },
+            funds,
+        )
+    }
+
+    pub fn query<T: DeserializeOwned>(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+        msg: &QueryMsg,
+    ) -> StdResult<T> {
+        querier.query_wasm_smart(&self.0, msg)
+    }
+
+    pub fn query_value(&self, querier: &QuerierWrapper<impl CustomQuery>) -> StdResult<ValueResp> {
+        self.query(querier, &QueryMsg::Value {})
+    }
+
+    pub fn query_pending_withdrawal(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+        id: u64,
+    ) -> StdResult<PendingWithdrawalResp> {
+        self.query(querier, &QueryMsg::PendingWithdrawal { id })
+    }
+
+    pub fn query_pending_withdrawals(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+    ) -> StdResult<PendingWithdrawalsResp> {
+        self.query(querier, &QueryMsg::PendingWithdrawals {})
+    }
+
+    pub fn query_withdrawal_delay(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+    ) -> StdResult<WithdrawalDelayResp> {
+        self.query(querier, &QueryMsg::WithdrawalDelay {})
+    }
+
+    pub fn query_cw20_tokens(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+    ) -> StdResult<Cw20TokensResp> {
+        self.query(querier, &QueryMsg::Cw20Tokens {})
+    }
+
+    pub fn query_receipt_contract(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+    ) -> StdResult<ReceiptContractResp> {
+        self.query(querier, &QueryMsg::ReceiptContract {})
+    }
+
+    pub fn query_allowed_prefixes(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+    ) -> StdResult<AllowedPrefixesResp> {
+        self.query(querier, &QueryMsg::AllowedPrefixes {})
+    }
+
+    pub fn query_migration_preview(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+    ) -> StdResult<MigrationPreviewResp> {
+        self.query(querier, &QueryMsg::MigrationPreview {})
+    }
+
+    pub fn query_donation(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+        id: u64,
+    ) -> StdResult<DonationResp> {
+        self.query(querier, &QueryMsg::Donation { id })
+    }
+
+    pub fn query_campaign(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+        id: u64,
+    ) -> StdResult<CampaignResp> {
+        self.query(querier, &QueryMsg::Campaign { id })
+    }
+
+    pub fn query_campaigns(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+    ) -> StdResult<CampaignsResp> {
+        self.query(querier, &QueryMsg::Campaigns {})
+    }
+
+    pub fn query_campaign_donation(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+        campaign: u64,
+        donor: &Addr,
+    ) -> StdResult<CampaignDonationResp> {
+        self.query(
+            querier,
+            &QueryMsg::CampaignDonation {
+                campaign,
+                donor: donor.to_string(),
+            },
+        )
+    }
+
+    pub fn query_matching(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+    ) -> StdResult<MatchingResp> {
+        self.query(querier, &QueryMsg::Matching {})
+    }
+
+    pub fn query_matched_donation(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+        donor: &Addr,
+    ) -> StdResult<MatchedDonationResp> {
+        self.query(
+            querier,
+            &QueryMsg::MatchedDonation {
+                donor: donor.to_string(),
+            },
+        )
+    }
+
+    pub fn query_stream(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+        id: u64,
+    ) -> StdResult<StreamResp> {
+        self.query(querier, &QueryMsg::Stream { id })
+    }
+
+    pub fn query_streams(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+    ) -> StdResult<StreamsResp> {
+        self.query(querier, &QueryMsg::Streams {})
+    }
+
+    pub fn query_ibc_channels(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+    ) -> StdResult<IbcChannelsResp> {
+        self.query(querier, &QueryMsg::IbcChannels {})
+    }
+
+    pub fn query_ibc_donation(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+        id: u64,
+    ) -> StdResult<IbcDonationResp> {
+        self.query(querier, &QueryMsg::IbcDonation { id })
+    }
+
+    #[cfg(feature = "cosmwasm_2")]
+    pub fn query_parent_transfers(
+        &self,
+        querier: &QuerierWrapper<impl CustomQuery>,
+    ) -> StdResult<ParentTransfersResp> {
+        self.query(querier, &QueryMsg::ParentTransfers {})
+    }
+}
@@ -1,18 +1,71 @@
+// Both CosmWasm stacks are optional dependencies, the enabled one is aliased to the usual crate
+// names so the rest of the code doesn't care which it builds against.
+#[cfg(all(feature = "cosmwasm_1", feature = "cosmwasm_2"))]
+compile_error!("features `cosmwasm_1` and `cosmwasm_2` are mutually exclusive");
+#[cfg(not(any(feature = "cosmwasm_1", feature = "cosmwasm_2")))]
+compile_error!("one of the features `cosmwasm_1` or `cosmwasm_2` has to be enabled");
+
+#[cfg(feature = "cosmwasm_2")]
+extern crate cosmwasm_schema_2 as cosmwasm_schema;
+#[cfg(feature = "cosmwasm_2")]
+extern crate cosmwasm_std_2 as cosmwasm_std;
+#[cfg(feature = "cosmwasm_2")]
+extern crate cw20_2 as cw20;
+#[cfg(feature = "cosmwasm_2")]
+extern crate cw2_2 as cw2;
+#[cfg(feature = "cosmwasm_2")]
+extern crate cw_storage_plus_2 as cw_storage_plus;
+
+#[cfg(all(feature = "tests", feature = "cosmwasm_2"))]
+compile_error!("the `cosmwasm_2` stack takes `tests_cosmwasm_2` instead of `tests`");
+#[cfg(all(feature = "tests_cosmwasm_2", not(feature = "cosmwasm_2")))]
+compile_error!("feature `tests_cosmwasm_2` needs the `cosmwasm_2` stack");
+
+#[cfg(all(any(test, feature = "tests"), not(feature = "cosmwasm_2")))]
+extern crate cw_multi_test_1 as cw_multi_test;
+#[cfg(all(any(test, feature = "tests_cosmwasm_2"), feature = "cosmwasm_2"))]
+extern crate cw_multi_test_2 as cw_multi_test;
+
+#[cfg(all(test, not(feature = "cosmwasm_2")))]
+extern crate cw20_base_1 as cw20_base;
+#[cfg(all(test, feature = "cosmwasm_2"))]
+extern crate cw20_base_2 as cw20_base;
+#[cfg(all(test, not(feature = "cosmwasm_2")))]
+extern crate cw721_1 as cw721;
+#[cfg(all(test, feature = "cosmwasm_2"))]
+extern crate cw721_2 as cw721;
+#[cfg(all(test, not(feature = "cosmwasm_2")))]
+extern crate cw721_base_1 as cw721_base;
+#[cfg(all(test, feature = "cosmwasm_2"))]
+extern crate cw721_base_2 as cw721_base;
+
+mod address;
+
 mod contract;
 
 pub mod error;
 
+pub mod helpers;
+
+mod migration;
+
 pub mod msg;
 
 mod state;
 
-#[cfg(any(test, feature = "tests"))]
+#[cfg(any(test, feature = "tests", feature = "tests_cosmwasm_2"))]
 pub mod multitest;
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
-use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, IbcBasicResponse, IbcChannelCloseMsg,
+    IbcChannelConnectMsg, IbcChannelOpenMsg, IbcChannelOpenResponse, IbcPacketAckMsg,
+    IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, MessageInfo, Response, StdResult,
+};
+#[cfg(feature = "cosmwasm_2")]
+use cosmwasm_std::{IbcSourceCallbackMsg, Reply};
 use error::ContractError;
 use msg::{ExecMsg, InstantiateMsg, MigrateMsg};
 
@@ -24,13 +77,13 @@ pub fn instantiate(
     _env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     contract::instantiate(deps, info, msg)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
-    contract::migrate(deps, msg.parent)
+    contract::migrate(deps, msg)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -43,23 +96,191 @@ pub fn execute(
     use ExecMsg::*;
 
     match msg {
-        Increment { value } => {
-            contract::exec::increment(deps, value, info).map_err(ContractError::from)
-        }
+        Increment { value } => contract::exec::increment(deps, value, info),
         Reset { value } => contract::exec::reset(deps, value, info),
-        Donate {} => contract::exec::donate(deps, env, info).map_err(ContractError::from),
+        Donate { campaign } => contract::exec::donate(deps, env, info, campaign),
+        Receive(msg) => contract::exec::receive_cw20(deps, env, info, msg),
+        AllowCw20 {
+            token,
+            minimal_donation,
+        } => contract::exec::allow_cw20(deps, info, token, minimal_donation),
+        SetReceiptContract { contract } => {
+            contract::exec::set_receipt_contract(deps, info, contract)
+        }
+        SetAllowedPrefixes { prefixes } => {
+            contract::exec::set_allowed_prefixes(deps, info, prefixes)
+        }
+        ConfigureMatching {
+            denom,
+            ratio,
+            per_donor_cap,
+            global_cap,
+            ends_at,
+        } => contract::exec::configure_matching(
+            deps,
+            env,
+            info,
+            denom,
+            ratio,
+            per_donor_cap,
+            global_cap,
+            ends_at,
+        ),
+        FundMatchingPool {} => contract::exec::fund_matching_pool(deps, env, info),
+        CreateCampaign {
+            targets,
+            start_time,
+            end_time,
+            refundable,
+        } => contract::exec::create_campaign(deps, info, targets, start_time, end_time, refundable),
+        ClaimRefund { campaign } => contract::exec::claim_refund(deps, env, info, campaign),
+        SetRefundGracePeriod { period } => {
+            contract::exec::set_refund_grace_period(deps, info, period)
+        }
+        RefundDonation { id } => contract::exec::refund_donation(deps, env, info, id),
         Withdraw {} => contract::exec::withdraw(deps, env, info),
-        WithdrawTo { receiver, funds } => {
-            contract::exec::withdraw_to(deps, env, info, receiver, funds)
+        WithdrawTo {
+            receiver,
+            funds,
+            cw20_funds,
+        } => contract::exec::withdraw_to(deps, env, info, receiver, funds, cw20_funds),
+        WithdrawSplit { recipients, funds } => {
+            contract::exec::withdraw_split(deps, env, info, recipients, funds)
+        }
+        RequestWithdrawal {
+            receiver,
+            funds,
+            cw20_funds,
+        } => contract::exec::request_withdrawal(deps, env, info, receiver, funds, cw20_funds),
+        CancelWithdrawal { id } => contract::exec::cancel_withdrawal(deps, env, info, id),
+        ExecuteWithdrawal { id } => contract::exec::execute_withdrawal(deps, env, info, id),
+        SetWithdrawalDelay { delay, guardian } => {
+            contract::exec::set_withdrawal_delay(deps, env, info, delay, guardian)
         }
+        CancelWithdrawalDelayChange {} => {
+            contract::exec::cancel_withdrawal_delay_change(deps, env, info)
+        }
+        CreateStream {
+            beneficiary,
+            denom,
+            amount_per_period,
+            period,
+            start_time,
+            end_time,
+        } => contract::exec::create_stream(
+            deps,
+            env,
+            info,
+            beneficiary,
+            denom,
+            amount_per_period,
+            period,
+            start_time,
+            end_time,
+        ),
+        ClaimStream { id } => contract::exec::claim_stream(deps, env, info, id),
+        DonateRemote { channel } => contract::exec::donate_remote(deps, env, info, channel),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
     use msg::QueryMsg::*;
 
     match msg {
-        Value {} => to_binary(&query::value(deps)?),
+        Value {} => to_json_binary(&query::value(deps)?),
+        PendingWithdrawal { id } => to_json_binary(&query::pending_withdrawal(deps, id)?),
+        PendingWithdrawals {} => to_json_binary(&query::pending_withdrawals(deps)?),
+        WithdrawalDelay {} => to_json_binary(&query::withdrawal_delay(deps, env)?),
+        Cw20Tokens {} => to_json_binary(&query::cw20_tokens(deps)?),
+        ReceiptContract {} => to_json_binary(&query::receipt_contract(deps)?),
+        AllowedPrefixes {} => to_json_binary(&query::allowed_prefixes(deps)?),
+        MigrationPreview {} => to_json_binary(&query::migration_preview(deps)?),
+        #[cfg(any(test, feature = "tests", feature = "tests_cosmwasm_2"))]
+        StorageKeys {} => to_json_binary(&query::storage_keys(deps)?),
+        Donation { id } => to_json_binary(&query::donation(deps, id)?),
+        Campaign { id } => to_json_binary(&query::campaign(deps, env, id)?),
+        Campaigns {} => to_json_binary(&query::campaigns(deps, env)?),
+        CampaignDonation { campaign, donor } => {
+            to_json_binary(&query::campaign_donation(deps, campaign, donor)?)
+        }
+        Matching {} => to_json_binary(&query::matching(deps)?),
+        MatchedDonation { donor } => to_json_binary(&query::matched_donation(deps, donor)?),
+        Stream { id } => to_json_binary(&query::stream(deps, env, id)?),
+        Streams {} => to_json_binary(&query::streams(deps, env)?),
+        IbcChannels {} => to_json_binary(&query::ibc_channels(deps)?),
+        IbcDonation { id } => to_json_binary(&query::ibc_donation(deps, id)?),
+        #[cfg(feature = "cosmwasm_2")]
+        ParentTransfers {} => to_json_binary(&query::parent_transfers(deps)?),
     }
 }
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_open(
+    _deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    contract::ibc::channel_open(msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    contract::ibc::channel_connect(deps, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_close(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    contract::ibc::channel_close(deps, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, ContractError> {
+    contract::ibc::packet_receive(deps, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_ack(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    contract::ibc::packet_ack(deps, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_timeout(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    contract::ibc::packet_timeout(deps, msg)
+}
+
+#[cfg(feature = "cosmwasm_2")]
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    contract::ibc::transfer_reply(deps, msg)
+}
+
+#[cfg(feature = "cosmwasm_2")]
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_source_callback(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcSourceCallbackMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    contract::ibc::source_callback(deps, msg)
+}
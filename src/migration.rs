@@ -0,0 +1,236 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Deps, StdResult, Storage};
+use cw_storage_plus::Item;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    contract::CONTRACT_VERSION,
+    error::ContractError,
+    msg::StoredState,
+    state::{State, PARENT_DONATION, STATE},
+};
+
+pub type MigrationFn = fn(&mut dyn Storage) -> StdResult<()>;
+pub type DecodeFn = fn(&dyn Storage) -> StdResult<StoredState>;
+
+pub struct MigrationStep {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub migrate: MigrationFn,
+    pub decode: DecodeFn,
+}
+
+// Steps have to stay sorted and chained, every step starts where the previous one ends.
+pub const MIGRATIONS: &[MigrationStep] = &[
+    MigrationStep {
+        from: "0.1.0",
+        to: "0.2.0",
+        migrate: migrate_0_1_0,
+        decode: decode_0_1_0,
+    },
+    MigrationStep {
+        from: "0.2.0",
+        to: "0.3.0",
+        migrate: migrate_0_2_0,
+        decode: decode_0_2_0,
+    },
+];
+
+#[derive(Deserialize, Serialize)]
+struct StateV0_2 {
+    counter: u64,
+    minimal_donation: Coin,
+    owner: Addr,
+}
+
+const COUNTER_V0_1: Item<u64> = Item::new("counter");
+const DONATION_V0_1: Item<Coin> = Item::new("donation");
+const OWNER_V0_1: Item<Addr> = Item::new("owner");
+const STATE_V0_2: Item<StateV0_2> = Item::new("state");
+
+pub fn parse_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version).map_err(|_| ContractError::InvalidVersion {
+        expected: CONTRACT_VERSION.into(),
+        found: version.into(),
+    })
+}
+
+// Pre-releases order before the release they precede, so `0.3.0-rc.1` still goes through the
+// `0.2.0 -> 0.3.0` step.
+pub fn migration_path(
+    from: &Version,
+    to: &Version,
+) -> Result<Vec<&'static MigrationStep>, ContractError> {
+    if from > to {
+        return Err(ContractError::DowngradeErr {
+            from: from.to_string(),
+            to: to.to_string(),
+        });
+    }
+
+    let oldest = MIGRATIONS
+        .first()
+        .map(|step| parse_version(step.from))
+        .transpose()?;
+
+    if oldest.is_some_and(|oldest| *from < oldest) {
+        return Err(ContractError::InvalidVersion {
+            expected: to.to_string(),
+            found: from.to_string(),
+        });
+    }
+
+    let mut path = vec![];
+    for step in MIGRATIONS {
+        let step_to = parse_version(step.to)?;
+
+        if *from < step_to && step_to <= *to {
+            path.push(step);
+        }
+    }
+
+    Ok(path)
+}
+
+pub struct MigrationPlan {
+    pub from: Version,
+    pub steps: Vec<&'static MigrationStep>,
+    pub before: StoredState,
+}
+
+impl MigrationPlan {
+    pub fn run(self, storage: &mut dyn Storage) -> StdResult<StoredState> {
+        for step in self.steps {
+            (step.migrate)(storage)?;
+        }
+
+        Ok(self.before)
+    }
+}
+
+pub fn plan(
+    storage: &dyn Storage,
+    from: Version,
+    to: &Version,
+) -> Result<MigrationPlan, ContractError> {
+    let steps = migration_path(&from, to)?;
+    let before = stored_state(storage, &steps)?;
+
+    Ok(MigrationPlan {
+        from,
+        steps,
+        before,
+    })
+}
+
+// The state as stored before the first step of the path, or the current one if nothing has to run.
+pub fn stored_state(storage: &dyn Storage, path: &[&MigrationStep]) -> StdResult<StoredState> {
+    match path.first() {
+        Some(step) => (step.decode)(storage),
+        None => decode_current(storage),
+    }
+}
+
+pub fn check_invariants(deps: Deps, before: &StoredState) -> Result<(), ContractError> {
+    let invariant = |reason: String| ContractError::MigrationInvariantErr { reason };
+
+    let state = STATE
+        .load(deps.storage)
+        .map_err(|err| invariant(format!("state is not readable: {err}")))?;
+
+    if state.counter != before.counter {
+        return Err(invariant(format!(
+            "counter changed from {} to {}",
+            before.counter, state.counter
+        )));
+    }
+
+    if deps.api.addr_validate(state.owner.as_str()).is_err() {
+        return Err(invariant(format!("owner {} is not valid", state.owner)));
+    }
+
+    let parent = PARENT_DONATION.may_load(deps.storage)?;
+    match (state.donating_parent, parent) {
+        (None, None) => (),
+        (Some(left), Some(parent)) => {
+            if parent.donating_parent_period == 0 || left > parent.donating_parent_period {
+                return Err(invariant(format!(
+                    "parent countdown {left} doesn't fit period {}",
+                    parent.donating_parent_period
+                )));
+            }
+
+            if parent.part > Decimal::one() {
+                return Err(invariant(format!(
+                    "parent part {} exceeds the donation",
+                    parent.part
+                )));
+            }
+        }
+        (Some(_), None) => return Err(invariant("parent countdown without a parent".into())),
+        (None, Some(_)) => return Err(invariant("parent without a countdown".into())),
+    }
+
+    Ok(())
+}
+
+pub fn decode_0_1_0(storage: &dyn Storage) -> StdResult<StoredState> {
+    Ok(StoredState {
+        counter: COUNTER_V0_1.load(storage)?,
+        minimal_donation: DONATION_V0_1.load(storage)?,
+        owner: OWNER_V0_1.load(storage)?,
+    })
+}
+
+pub fn decode_0_2_0(storage: &dyn Storage) -> StdResult<StoredState> {
+    let state = STATE_V0_2.load(storage)?;
+
+    Ok(StoredState {
+        counter: state.counter,
+        minimal_donation: state.minimal_donation,
+        owner: state.owner,
+    })
+}
+
+pub fn decode_current(storage: &dyn Storage) -> StdResult<StoredState> {
+    let state = STATE.load(storage)?;
+
+    Ok(StoredState {
+        counter: state.counter,
+        minimal_donation: state.minimal_donation,
+        owner: state.owner,
+    })
+}
+
+pub fn migrate_0_1_0(storage: &mut dyn Storage) -> StdResult<()> {
+    let StoredState {
+        counter,
+        minimal_donation,
+        owner,
+    } = decode_0_1_0(storage)?;
+
+    STATE_V0_2.save(
+        storage,
+        &StateV0_2 {
+            counter,
+            minimal_donation,
+            owner,
+        },
+    )?;
+
+    COUNTER_V0_1.remove(storage);
+    DONATION_V0_1.remove(storage);
+    OWNER_V0_1.remove(storage);
+
+    Ok(())
+}
+
+pub fn migrate_0_2_0(storage: &mut dyn Storage) -> StdResult<()> {
+    let StateV0_2 {
+        counter,
+        minimal_donation,
+        owner,
+    } = STATE_V0_2.load(storage)?;
+
+    STATE.save(storage, &State::new(counter, minimal_donation, owner, None))
+}
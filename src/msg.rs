@@ -1,5 +1,6 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Coin, Decimal};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Timestamp, Uint128};
+use cw20::{Cw20Coin, Cw20ReceiveMsg};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -7,6 +8,9 @@ pub struct InstantiateMsg {
     pub counter: u64,
     pub minimal_donation: Coin,
     pub parent: Option<Parent>,
+    pub withdrawal_delay: Option<WithdrawalDelay>,
+    #[serde(default)]
+    pub allowed_prefixes: Vec<AllowedPrefix>,
 }
 
 impl InstantiateMsg {
@@ -15,15 +19,39 @@ impl InstantiateMsg {
             counter,
             minimal_donation,
             parent,
+            withdrawal_delay: None,
+            allowed_prefixes: vec![],
         }
     }
 }
 
+#[cw_serde]
+pub struct AllowedPrefix {
+    pub prefix: String,
+    pub lengths: Vec<u32>,
+    pub channel: String,
+}
+
+#[cw_serde]
+pub struct WithdrawalDelay {
+    pub delay: u64,
+    pub guardian: Option<String>,
+}
+
 #[cw_serde]
 pub struct Parent {
     pub addr: String,
     pub donating_period: u64,
     pub part: Decimal,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteParent>,
+}
+
+#[cw_serde]
+#[derive(Eq)]
+pub struct RemoteParent {
+    pub channel: String,
+    pub timeout: u64,
 }
 
 #[cw_serde]
@@ -46,6 +74,27 @@ impl InstantiateResp {
 #[cw_serde]
 pub struct MigrateMsg {
     pub parent: Option<Parent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub force_from_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_override: Option<StateOverride>,
+}
+
+impl MigrateMsg {
+    pub fn new(parent: Option<Parent>) -> Self {
+        Self {
+            parent,
+            force_from_version: None,
+            state_override: None,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct StateOverride {
+    pub counter: u64,
+    pub minimal_donation: Coin,
+    pub owner: String,
 }
 
 #[cw_serde]
@@ -57,14 +106,126 @@ pub enum ExecMsg {
         #[serde(default)]
         value: u64,
     },
-    Donate {},
+    Donate {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        campaign: Option<u64>,
+    },
+    Receive(Cw20ReceiveMsg),
+    AllowCw20 {
+        token: String,
+        minimal_donation: Uint128,
+    },
+    SetReceiptContract {
+        contract: Option<String>,
+    },
+    SetAllowedPrefixes {
+        prefixes: Vec<AllowedPrefix>,
+    },
+    ConfigureMatching {
+        denom: String,
+        ratio: Decimal,
+        per_donor_cap: Uint128,
+        global_cap: Uint128,
+        ends_at: Timestamp,
+    },
+    FundMatchingPool {},
+    CreateCampaign {
+        targets: Vec<Coin>,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        refundable: bool,
+    },
+    ClaimRefund {
+        campaign: u64,
+    },
+    SetRefundGracePeriod {
+        period: Option<u64>,
+    },
+    RefundDonation {
+        id: u64,
+    },
     Withdraw {},
     WithdrawTo {
         receiver: String,
         funds: Vec<Coin>,
+        #[serde(default)]
+        cw20_funds: Vec<Cw20Coin>,
+    },
+    WithdrawSplit {
+        recipients: Vec<(String, Decimal)>,
+        #[serde(default)]
+        funds: Vec<Coin>,
+    },
+    RequestWithdrawal {
+        receiver: String,
+        funds: Vec<Coin>,
+        #[serde(default)]
+        cw20_funds: Vec<Cw20Coin>,
+    },
+    CancelWithdrawal {
+        id: u64,
+    },
+    ExecuteWithdrawal {
+        id: u64,
+    },
+    SetWithdrawalDelay {
+        delay: u64,
+        guardian: Option<String>,
+    },
+    CancelWithdrawalDelayChange {},
+    CreateStream {
+        beneficiary: String,
+        denom: String,
+        amount_per_period: Uint128,
+        period: u64,
+        start_time: Timestamp,
+        end_time: Timestamp,
+    },
+    ClaimStream {
+        id: u64,
+    },
+    DonateRemote {
+        channel: String,
     },
 }
 
+#[cw_serde]
+pub enum ReceiveMsg {
+    Donate {},
+}
+
+#[cw_serde]
+pub struct ReceiptMetadata {
+    pub donor: Addr,
+    pub amount: Vec<Coin>,
+    pub counter: u64,
+}
+
+#[cw_serde]
+pub enum IbcPacketMsg {
+    Donate {
+        id: u64,
+        donor: String,
+        funds: Vec<Coin>,
+    },
+}
+
+#[cw_serde]
+pub enum IbcAck {
+    Result(Binary),
+    Error(String),
+}
+
+#[cw_serde]
+pub struct Ics20Packet {
+    pub denom: String,
+    pub amount: Uint128,
+    pub sender: String,
+    pub receiver: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
 #[cw_serde]
 pub struct IncrementResp {
     pub value: u64,
@@ -92,9 +253,227 @@ impl DonateResp {
 pub enum QueryMsg {
     #[returns(ValueResp)]
     Value {},
+    #[returns(PendingWithdrawalResp)]
+    PendingWithdrawal { id: u64 },
+    #[returns(PendingWithdrawalsResp)]
+    PendingWithdrawals {},
+    #[returns(WithdrawalDelayResp)]
+    WithdrawalDelay {},
+    #[returns(Cw20TokensResp)]
+    Cw20Tokens {},
+    #[returns(ReceiptContractResp)]
+    ReceiptContract {},
+    #[returns(AllowedPrefixesResp)]
+    AllowedPrefixes {},
+    #[returns(MigrationPreviewResp)]
+    MigrationPreview {},
+    #[cfg(any(test, feature = "tests", feature = "tests_cosmwasm_2"))]
+    #[returns(StorageKeysResp)]
+    StorageKeys {},
+    #[returns(DonationResp)]
+    Donation { id: u64 },
+    #[returns(CampaignResp)]
+    Campaign { id: u64 },
+    #[returns(CampaignsResp)]
+    Campaigns {},
+    #[returns(CampaignDonationResp)]
+    CampaignDonation { campaign: u64, donor: String },
+    #[returns(MatchingResp)]
+    Matching {},
+    #[returns(MatchedDonationResp)]
+    MatchedDonation { donor: String },
+    #[returns(StreamResp)]
+    Stream { id: u64 },
+    #[returns(StreamsResp)]
+    Streams {},
+    #[returns(IbcChannelsResp)]
+    IbcChannels {},
+    #[returns(IbcDonationResp)]
+    IbcDonation { id: u64 },
+    // Only on the 2.x stack, where ICS-20 reports back through IBC callbacks. Those need the ADR-8
+    // callbacks middleware on the transfer stack, without it transfers are never settled.
+    #[cfg(feature = "cosmwasm_2")]
+    #[returns(ParentTransfersResp)]
+    ParentTransfers {},
 }
 
 #[cw_serde]
 pub struct ValueResp {
     pub value: u64,
 }
+
+#[cw_serde]
+pub struct PendingWithdrawalResp {
+    pub id: u64,
+    pub receiver: Addr,
+    pub funds: Vec<Coin>,
+    pub cw20_funds: Vec<Cw20Coin>,
+    pub release_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct PendingWithdrawalsResp {
+    pub withdrawals: Vec<PendingWithdrawalResp>,
+}
+
+#[cw_serde]
+pub struct WithdrawalDelayResp {
+    pub delay: u64,
+    pub guardian: Option<Addr>,
+    pub pending: Option<PendingWithdrawalDelayResp>,
+}
+
+#[cw_serde]
+pub struct PendingWithdrawalDelayResp {
+    pub delay: u64,
+    pub guardian: Option<Addr>,
+    pub effective_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct Cw20TokenResp {
+    pub token: Addr,
+    pub minimal_donation: Uint128,
+}
+
+#[cw_serde]
+pub struct Cw20TokensResp {
+    pub tokens: Vec<Cw20TokenResp>,
+}
+
+#[cw_serde]
+pub struct DonationResp {
+    pub id: u64,
+    pub donor: Addr,
+    pub funds: Vec<Coin>,
+    pub refundable_until: Timestamp,
+}
+
+#[cw_serde]
+pub enum CampaignStatus {
+    Upcoming,
+    Active,
+    Succeeded,
+    Failed,
+}
+
+#[cw_serde]
+pub struct CampaignResp {
+    pub id: u64,
+    pub targets: Vec<Coin>,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub refundable: bool,
+    pub raised: Vec<Coin>,
+    pub refunded: Vec<Coin>,
+    pub status: CampaignStatus,
+}
+
+#[cw_serde]
+pub struct CampaignsResp {
+    pub campaigns: Vec<CampaignResp>,
+}
+
+#[cw_serde]
+pub struct CampaignDonationResp {
+    pub donated: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct MatchingResp {
+    pub denom: String,
+    pub ratio: Decimal,
+    pub per_donor_cap: Uint128,
+    pub global_cap: Uint128,
+    pub ends_at: Timestamp,
+    pub pool_remaining: Uint128,
+    pub matched_total: Uint128,
+}
+
+#[cw_serde]
+pub struct MatchedDonationResp {
+    pub matched: Uint128,
+}
+
+#[cw_serde]
+pub struct StoredState {
+    pub counter: u64,
+    pub minimal_donation: Coin,
+    pub owner: Addr,
+}
+
+#[cw_serde]
+pub struct MigrationStepResp {
+    pub from: String,
+    pub to: String,
+}
+
+#[cw_serde]
+pub struct MigrationPreviewResp {
+    pub stored_version: String,
+    pub target_version: String,
+    pub steps: Vec<MigrationStepResp>,
+    pub stored_state: StoredState,
+}
+
+#[cw_serde]
+pub struct StorageKeysResp {
+    pub keys: Vec<String>,
+}
+
+#[cw_serde]
+pub struct AllowedPrefixesResp {
+    pub prefixes: Vec<AllowedPrefix>,
+}
+
+#[cw_serde]
+pub struct ReceiptContractResp {
+    pub contract: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct StreamResp {
+    pub id: u64,
+    pub beneficiary: Addr,
+    pub denom: String,
+    pub amount_per_period: Uint128,
+    pub period: u64,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub vested: Uint128,
+    pub claimed: Uint128,
+}
+
+#[cw_serde]
+pub struct StreamsResp {
+    pub streams: Vec<StreamResp>,
+}
+
+#[cw_serde]
+pub struct IbcChannelsResp {
+    pub channels: Vec<String>,
+}
+
+#[cw_serde]
+pub struct IbcDonationResp {
+    pub id: u64,
+    pub donor: Addr,
+    pub channel: String,
+    pub funds: Vec<Coin>,
+}
+
+#[cfg(feature = "cosmwasm_2")]
+#[cw_serde]
+pub struct ParentTransferResp {
+    pub channel: String,
+    pub sequence: u64,
+    pub receiver: String,
+    pub amount: Coin,
+    pub timeout: Timestamp,
+}
+
+#[cfg(feature = "cosmwasm_2")]
+#[cw_serde]
+pub struct ParentTransfersResp {
+    pub transfers: Vec<ParentTransferResp>,
+}
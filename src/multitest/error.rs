@@ -0,0 +1,71 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+use crate::error::ContractError;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MultitestError {
+    #[error("{0}")]
+    Contract(#[from] ContractError),
+
+    // The bank module reports missing funds as a balance overflow.
+    #[error("bank: {0}")]
+    Bank(StdError),
+
+    #[error("wasm: {0}")]
+    Wasm(#[from] cw_multi_test::error::Error),
+
+    #[error("{0}")]
+    Std(StdError),
+
+    #[error("{0}")]
+    Unknown(String),
+}
+
+impl MultitestError {
+    pub fn contract(&self) -> Option<&ContractError> {
+        match self {
+            Self::Contract(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<StdError> for MultitestError {
+    fn from(err: StdError) -> Self {
+        Self::Std(err)
+    }
+}
+
+impl From<anyhow::Error> for MultitestError {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<ContractError>() {
+            Ok(err) => return Self::Contract(err),
+            Err(err) => err,
+        };
+
+        let err = match err.downcast::<cw_multi_test::error::Error>() {
+            Ok(err) => return Self::Wasm(err),
+            Err(err) => err,
+        };
+
+        match err.downcast::<StdError>() {
+            Ok(err @ StdError::Overflow { .. }) => Self::Bank(err),
+            Ok(err) => Self::Std(err),
+            Err(err) => Self::Unknown(format!("{err:#}")),
+        }
+    }
+}
+
+// Lets tests compare helper errors directly against the contract error they expect.
+impl PartialEq<ContractError> for MultitestError {
+    fn eq(&self, other: &ContractError) -> bool {
+        self.contract() == Some(other)
+    }
+}
+
+impl PartialEq<MultitestError> for ContractError {
+    fn eq(&self, other: &MultitestError) -> bool {
+        other == self
+    }
+}
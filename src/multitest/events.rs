@@ -0,0 +1,233 @@
+use std::fmt;
+
+use cosmwasm_std::{Addr, Event};
+
+// Attribute multi-test adds to every event emitted on behalf of a contract.
+#[cfg(not(feature = "cosmwasm_2"))]
+const CONTRACT_ATTR: &str = "_contract_addr";
+#[cfg(feature = "cosmwasm_2")]
+const CONTRACT_ATTR: &str = "_contract_address";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum AttrMatcher {
+    Value(String, String),
+    Key(String),
+    NoValue(String, String),
+    NoKey(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventMatcher {
+    ty: String,
+    attrs: Vec<AttrMatcher>,
+}
+
+impl EventMatcher {
+    pub fn new(ty: impl Into<String>) -> Self {
+        Self {
+            ty: ty.into(),
+            attrs: vec![],
+        }
+    }
+
+    // The event holding the attributes set directly on the `Response`.
+    pub fn wasm() -> Self {
+        Self::new("wasm")
+    }
+
+    // Events added with `Response::add_event` are prefixed with `wasm-`.
+    pub fn custom(ty: &str) -> Self {
+        Self::new(format!("wasm-{ty}"))
+    }
+
+    pub fn contract(self, addr: &Addr) -> Self {
+        self.attr(CONTRACT_ATTR, addr.as_str())
+    }
+
+    pub fn action(self, action: &str) -> Self {
+        self.attr("action", action)
+    }
+
+    pub fn attr(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attrs
+            .push(AttrMatcher::Value(key.into(), value.into()));
+        self
+    }
+
+    pub fn has_key(mut self, key: impl Into<String>) -> Self {
+        self.attrs.push(AttrMatcher::Key(key.into()));
+        self
+    }
+
+    pub fn lacks_attr(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attrs
+            .push(AttrMatcher::NoValue(key.into(), value.into()));
+        self
+    }
+
+    pub fn lacks_key(mut self, key: impl Into<String>) -> Self {
+        self.attrs.push(AttrMatcher::NoKey(key.into()));
+        self
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        event.ty == self.ty && self.diff(event).is_empty()
+    }
+
+    // Lines describing why `event` does not match, `-` for what was expected and `+` for what was found.
+    fn diff(&self, event: &Event) -> Vec<String> {
+        let values = |key: &str| -> Vec<&str> {
+            event
+                .attributes
+                .iter()
+                .filter(|attr| attr.key == key)
+                .map(|attr| attr.value.as_str())
+                .collect()
+        };
+
+        let mut lines = vec![];
+        for attr in &self.attrs {
+            match attr {
+                AttrMatcher::Value(key, value) => {
+                    let found = values(key);
+                    if !found.contains(&value.as_str()) {
+                        lines.push(format!("- {key}={value:?}"));
+                        lines.extend(found.iter().map(|found| format!("+ {key}={found:?}")));
+                    }
+                }
+                AttrMatcher::Key(key) => {
+                    if values(key).is_empty() {
+                        lines.push(format!("- {key}"));
+                    }
+                }
+                AttrMatcher::NoValue(key, value) => {
+                    if values(key).contains(&value.as_str()) {
+                        lines.push(format!("+ {key}={value:?} (unexpected)"));
+                    }
+                }
+                AttrMatcher::NoKey(key) => {
+                    lines.extend(
+                        values(key)
+                            .iter()
+                            .map(|found| format!("+ {key}={found:?} (unexpected)")),
+                    );
+                }
+            }
+        }
+
+        lines
+    }
+}
+
+impl fmt::Display for EventMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {{", self.ty)?;
+        for (idx, attr) in self.attrs.iter().enumerate() {
+            let sep = if idx == 0 { " " } else { ", " };
+            match attr {
+                AttrMatcher::Value(key, value) => write!(f, "{sep}{key}={value:?}")?,
+                AttrMatcher::Key(key) => write!(f, "{sep}{key}=*")?,
+                AttrMatcher::NoValue(key, value) => write!(f, "{sep}{key}!={value:?}")?,
+                AttrMatcher::NoKey(key) => write!(f, "{sep}!{key}")?,
+            }
+        }
+        write!(f, " }}")
+    }
+}
+
+pub fn assert_events(events: &[Event]) -> EventsAssert<'_> {
+    EventsAssert(events)
+}
+
+pub struct EventsAssert<'a>(&'a [Event]);
+
+impl<'a> EventsAssert<'a> {
+    pub fn find(&self, matcher: &EventMatcher) -> Vec<&'a Event> {
+        self.0
+            .iter()
+            .filter(|event| matcher.matches(event))
+            .collect()
+    }
+
+    #[track_caller]
+    pub fn contains(self, matcher: EventMatcher) -> Self {
+        if self.find(&matcher).is_empty() {
+            panic!("no event matches {matcher}\n{}", self.report(&[&matcher]));
+        }
+        self
+    }
+
+    #[track_caller]
+    pub fn not_contains(self, matcher: EventMatcher) -> Self {
+        if let Some(idx) = self.0.iter().position(|event| matcher.matches(event)) {
+            panic!(
+                "event #{idx} unexpectedly matches {matcher}\n{}",
+                self.report(&[])
+            );
+        }
+        self
+    }
+
+    #[track_caller]
+    pub fn count(self, matcher: EventMatcher, expected: usize) -> Self {
+        let found = self.find(&matcher).len();
+        if found != expected {
+            panic!(
+                "expected {expected} events matching {matcher}, found {found}\n{}",
+                self.report(&[&matcher])
+            );
+        }
+        self
+    }
+
+    // Every matcher has to match a distinct event, in the given order. Other events may sit in
+    // between, so a contract's event can be asserted to come before those of its submessages.
+    #[track_caller]
+    pub fn in_order(self, matchers: impl IntoIterator<Item = EventMatcher>) -> Self {
+        let mut events = self.0.iter().enumerate();
+        let mut last = None;
+        for matcher in matchers {
+            match events.find(|(_, event)| matcher.matches(event)) {
+                Some((idx, _)) => last = Some(idx),
+                None => {
+                    let after = match last {
+                        Some(idx) => format!("after event #{idx}"),
+                        None => "at all".to_owned(),
+                    };
+                    panic!(
+                        "no event matches {matcher} {after}\n{}",
+                        self.report(&[&matcher])
+                    );
+                }
+            }
+        }
+        self
+    }
+
+    // Lists all events, annotating those of a matcher's type with how they differ from it.
+    fn report(&self, matchers: &[&EventMatcher]) -> String {
+        let mut report = "events:".to_owned();
+        for (idx, event) in self.0.iter().enumerate() {
+            report.push_str(&format!("\n  #{idx} {}", DisplayEvent(event)));
+            for matcher in matchers.iter().filter(|matcher| matcher.ty == event.ty) {
+                for line in matcher.diff(event) {
+                    report.push_str(&format!("\n      {line}"));
+                }
+            }
+        }
+        report
+    }
+}
+
+struct DisplayEvent<'a>(&'a Event);
+
+impl fmt::Display for DisplayEvent<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {{", self.0.ty)?;
+        for (idx, attr) in self.0.attributes.iter().enumerate() {
+            let sep = if idx == 0 { " " } else { ", " };
+            write!(f, "{sep}{}={:?}", attr.key, attr.value)?;
+        }
+        write!(f, " }}")
+    }
+}
@@ -1,16 +1,33 @@
+mod error;
+mod events;
+#[cfg(test)]
+mod model;
+mod suite;
 #[cfg(test)]
 mod tests;
 
-use cosmwasm_std::{Addr, Attribute, Coin, Event, StdResult};
+#[cfg(feature = "cosmwasm_2")]
+use cosmwasm_std::testing::MockApi;
+use cosmwasm_std::{coin, to_json_binary, Addr, Coin, Decimal, StdResult, Timestamp, Uint128};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
 use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
 
 use crate::{
-    error::ContractError,
     execute, instantiate, migrate,
-    msg::{ExecMsg, InstantiateMsg, MigrateMsg, Parent, QueryMsg, ValueResp},
+    msg::{
+        CampaignResp, Cw20TokensResp, DonationResp, ExecMsg, InstantiateMsg, MatchedDonationResp,
+        MatchingResp, MigrateMsg, Parent, PendingWithdrawalResp, PendingWithdrawalsResp, QueryMsg,
+        ReceiptContractResp, ReceiveMsg, StorageKeysResp, StreamResp, StreamsResp, ValueResp,
+        WithdrawalDelayResp,
+    },
     query,
 };
 
+pub use error::MultitestError;
+pub use events::{assert_events, EventMatcher, EventsAssert};
+pub use suite::{CountingSuite, CountingSuiteBuilder};
+
+#[derive(Debug)]
 pub struct CountingContract(Addr);
 
 impl CountingContract {
@@ -31,47 +48,35 @@ impl CountingContract {
         label: &str,
         counter: impl Into<Option<u64>>,
         minimal_donation: Coin,
-    ) -> StdResult<CountingContract> {
-        Self::instantiate_with_funds_admin(
+    ) -> Result<CountingContract, MultitestError> {
+        let counter = counter.into().unwrap_or_default();
+
+        Self::instantiate_with_msg(
             app,
             code_id,
             sender,
             label,
-            counter,
-            minimal_donation,
+            &InstantiateMsg::new(counter, minimal_donation, None),
             &[],
             None,
-            None,
         )
     }
 
     #[track_caller]
-    #[allow(clippy::too_many_arguments)]
-    pub fn instantiate_with_funds_admin(
+    pub fn instantiate_with_msg(
         app: &mut App,
         code_id: u64,
         sender: Addr,
         label: &str,
-        counter: impl Into<Option<u64>>,
-        minimal_donation: Coin,
+        msg: &InstantiateMsg,
         send_funds: &[Coin],
         admin: impl Into<Option<String>>,
-        parent: impl Into<Option<Parent>>,
-    ) -> StdResult<CountingContract> {
-        let counter = counter.into().unwrap_or_default();
+    ) -> Result<CountingContract, MultitestError> {
         let admin = admin.into();
-        let parent = parent.into();
 
-        app.instantiate_contract(
-            code_id,
-            sender,
-            &InstantiateMsg::new(counter, minimal_donation, parent),
-            send_funds,
-            label,
-            admin,
-        )
-        .map_err(|e| e.downcast().unwrap())
-        .map(CountingContract)
+        app.instantiate_contract(code_id, sender, msg, send_funds, label, admin)
+            .map_err(MultitestError::from)
+            .map(CountingContract)
     }
 
     #[track_caller]
@@ -81,15 +86,15 @@ impl CountingContract {
         code_id: u64,
         sender: Addr,
         parent: impl Into<Option<Parent>>,
-    ) -> StdResult<Self> {
+    ) -> Result<Self, MultitestError> {
         let parent = parent.into();
         app.migrate_contract(
             sender,
             contract_addr.clone(),
-            &MigrateMsg { parent },
+            &MigrateMsg::new(parent),
             code_id,
         )
-        .map_err(|e| e.downcast().unwrap())
+        .map_err(MultitestError::from)
         .map(|_| Self(contract_addr))
     }
 
@@ -98,6 +103,81 @@ impl CountingContract {
             .query_wasm_smart(self.addr(), &QueryMsg::Value {})
     }
 
+    pub fn query_storage_keys(&self, app: &App) -> StdResult<StorageKeysResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::StorageKeys {})
+    }
+
+    pub fn query_pending_withdrawal(&self, app: &App, id: u64) -> StdResult<PendingWithdrawalResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::PendingWithdrawal { id })
+    }
+
+    pub fn query_pending_withdrawals(&self, app: &App) -> StdResult<PendingWithdrawalsResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::PendingWithdrawals {})
+    }
+
+    pub fn query_withdrawal_delay(&self, app: &App) -> StdResult<WithdrawalDelayResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::WithdrawalDelay {})
+    }
+
+    pub fn query_cw20_tokens(&self, app: &App) -> StdResult<Cw20TokensResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::Cw20Tokens {})
+    }
+
+    pub fn query_cw20_balance(app: &App, token: &Addr, addr: Addr) -> StdResult<Uint128> {
+        let resp: BalanceResponse = app.wrap().query_wasm_smart(
+            token,
+            &Cw20QueryMsg::Balance {
+                address: addr.into_string(),
+            },
+        )?;
+
+        Ok(resp.balance)
+    }
+
+    pub fn query_receipt_contract(&self, app: &App) -> StdResult<ReceiptContractResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::ReceiptContract {})
+    }
+
+    pub fn query_donation(&self, app: &App, id: u64) -> StdResult<DonationResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::Donation { id })
+    }
+
+    pub fn query_campaign(&self, app: &App, id: u64) -> StdResult<CampaignResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::Campaign { id })
+    }
+
+    pub fn query_matching(&self, app: &App) -> StdResult<MatchingResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::Matching {})
+    }
+
+    pub fn query_matched_donation(&self, app: &App, donor: Addr) -> StdResult<MatchedDonationResp> {
+        app.wrap().query_wasm_smart(
+            self.addr(),
+            &QueryMsg::MatchedDonation {
+                donor: donor.into_string(),
+            },
+        )
+    }
+
+    pub fn query_stream(&self, app: &App, id: u64) -> StdResult<StreamResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::Stream { id })
+    }
+
+    pub fn query_streams(&self, app: &App) -> StdResult<StreamsResp> {
+        app.wrap()
+            .query_wasm_smart(self.addr(), &QueryMsg::Streams {})
+    }
+
     pub fn query_balance(app: &App, addr: Addr, denation: &str) -> StdResult<Coin> {
         app.wrap().query_balance(addr, denation)
     }
@@ -108,15 +188,186 @@ impl CountingContract {
         app: &mut App,
         sender: Addr,
         funds: &[Coin],
-    ) -> Result<AppResponse, ContractError> {
-        app.execute_contract(sender, self.addr(), &ExecMsg::Donate {}, funds)
-            .map_err(|e| e.downcast().unwrap())
+    ) -> Result<AppResponse, MultitestError> {
+        self.donate_to_campaign(app, sender, None, funds)
+    }
+
+    #[track_caller]
+    pub fn donate_to_campaign(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        campaign: impl Into<Option<u64>>,
+        funds: &[Coin],
+    ) -> Result<AppResponse, MultitestError> {
+        let campaign = campaign.into();
+        app.execute_contract(sender, self.addr(), &ExecMsg::Donate { campaign }, funds)
+            .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn set_refund_grace_period(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        period: impl Into<Option<u64>>,
+    ) -> Result<AppResponse, MultitestError> {
+        let period = period.into();
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecMsg::SetRefundGracePeriod { period },
+            &[],
+        )
+        .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn refund_donation(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        id: u64,
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(sender, self.addr(), &ExecMsg::RefundDonation { id }, &[])
+            .map_err(MultitestError::from)
     }
 
     #[track_caller]
-    pub fn withdraw(&self, app: &mut App, sender: Addr) -> Result<AppResponse, ContractError> {
+    pub fn donate_cw20(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        token: &Addr,
+        amount: u128,
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(
+            sender,
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: self.addr().into_string(),
+                amount: amount.into(),
+                msg: to_json_binary(&ReceiveMsg::Donate {})?,
+            },
+            &[],
+        )
+        .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn allow_cw20(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        token: &Addr,
+        minimal_donation: u128,
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecMsg::AllowCw20 {
+                token: token.to_string(),
+                minimal_donation: minimal_donation.into(),
+            },
+            &[],
+        )
+        .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn set_receipt_contract(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        contract: impl Into<Option<String>>,
+    ) -> Result<AppResponse, MultitestError> {
+        let contract = contract.into();
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecMsg::SetReceiptContract { contract },
+            &[],
+        )
+        .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure_matching(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        denom: &str,
+        ratio: Decimal,
+        per_donor_cap: u128,
+        global_cap: u128,
+        ends_at: Timestamp,
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecMsg::ConfigureMatching {
+                denom: denom.into(),
+                ratio,
+                per_donor_cap: per_donor_cap.into(),
+                global_cap: global_cap.into(),
+                ends_at,
+            },
+            &[],
+        )
+        .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn fund_matching_pool(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        funds: &[Coin],
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(sender, self.addr(), &ExecMsg::FundMatchingPool {}, funds)
+            .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn create_campaign(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        targets: Vec<Coin>,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        refundable: bool,
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecMsg::CreateCampaign {
+                targets,
+                start_time,
+                end_time,
+                refundable,
+            },
+            &[],
+        )
+        .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn claim_refund(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        campaign: u64,
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(sender, self.addr(), &ExecMsg::ClaimRefund { campaign }, &[])
+            .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn withdraw(&self, app: &mut App, sender: Addr) -> Result<AppResponse, MultitestError> {
         app.execute_contract(sender, self.addr(), &ExecMsg::Withdraw {}, &[])
-            .map_err(|e| e.downcast().unwrap())
+            .map_err(MultitestError::from)
     }
 
     #[track_caller]
@@ -126,17 +377,163 @@ impl CountingContract {
         sender: Addr,
         receiver: String,
         send_funds: Vec<Coin>,
-    ) -> Result<AppResponse, ContractError> {
+    ) -> Result<AppResponse, MultitestError> {
+        self.withdraw_to_with_cw20(app, sender, receiver, send_funds, vec![])
+    }
+
+    #[track_caller]
+    pub fn withdraw_to_with_cw20(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        receiver: String,
+        send_funds: Vec<Coin>,
+        cw20_funds: Vec<Cw20Coin>,
+    ) -> Result<AppResponse, MultitestError> {
         app.execute_contract(
             sender,
             self.addr(),
             &ExecMsg::WithdrawTo {
                 receiver,
                 funds: send_funds,
+                cw20_funds,
             },
             &[],
         )
-        .map_err(|e| e.downcast().unwrap())
+        .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn withdraw_split(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        recipients: Vec<(String, Decimal)>,
+        funds: Vec<Coin>,
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecMsg::WithdrawSplit { recipients, funds },
+            &[],
+        )
+        .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn request_withdrawal(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        receiver: String,
+        funds: Vec<Coin>,
+        cw20_funds: Vec<Cw20Coin>,
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecMsg::RequestWithdrawal {
+                receiver,
+                funds,
+                cw20_funds,
+            },
+            &[],
+        )
+        .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn cancel_withdrawal(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        id: u64,
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(sender, self.addr(), &ExecMsg::CancelWithdrawal { id }, &[])
+            .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn execute_withdrawal(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        id: u64,
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(sender, self.addr(), &ExecMsg::ExecuteWithdrawal { id }, &[])
+            .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn set_withdrawal_delay(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        delay: u64,
+        guardian: impl Into<Option<String>>,
+    ) -> Result<AppResponse, MultitestError> {
+        let guardian = guardian.into();
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecMsg::SetWithdrawalDelay { delay, guardian },
+            &[],
+        )
+        .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn cancel_withdrawal_delay_change(
+        &self,
+        app: &mut App,
+        sender: Addr,
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecMsg::CancelWithdrawalDelayChange {},
+            &[],
+        )
+        .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stream(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        beneficiary: String,
+        amount_per_period: Coin,
+        period: u64,
+        start_time: Timestamp,
+        end_time: Timestamp,
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(
+            sender,
+            self.addr(),
+            &ExecMsg::CreateStream {
+                beneficiary,
+                denom: amount_per_period.denom,
+                amount_per_period: amount_per_period.amount,
+                period,
+                start_time,
+                end_time,
+            },
+            &[],
+        )
+        .map_err(MultitestError::from)
+    }
+
+    #[track_caller]
+    pub fn claim_stream(
+        &self,
+        app: &mut App,
+        sender: Addr,
+        id: u64,
+    ) -> Result<AppResponse, MultitestError> {
+        app.execute_contract(sender, self.addr(), &ExecMsg::ClaimStream { id }, &[])
+            .map_err(MultitestError::from)
     }
 
     #[track_caller]
@@ -145,9 +542,9 @@ impl CountingContract {
         app: &mut App,
         sender: Addr,
         value: u64,
-    ) -> Result<AppResponse, ContractError> {
+    ) -> Result<AppResponse, MultitestError> {
         app.execute_contract(sender, self.addr(), &ExecMsg::Increment { value }, &[])
-            .map_err(|e| e.downcast().unwrap())
+            .map_err(MultitestError::from)
     }
 
     #[track_caller]
@@ -156,37 +553,54 @@ impl CountingContract {
         app: &mut App,
         sender: Addr,
         value: u64,
-    ) -> Result<AppResponse, ContractError> {
+    ) -> Result<AppResponse, MultitestError> {
         app.execute_contract(sender, self.addr(), &ExecMsg::Reset { value }, &[])
-            .map_err(|e| e.downcast().unwrap())
-    }
-
-    #[track_caller]
-    pub fn verify_events(events: Vec<Event>, action: &str, sender: &str) -> bool {
-        let wasm_event = events.iter().find(|e| e.ty == "wasm").unwrap();
-
-        let b = vec![
-            Attribute::new("action", action),
-            Attribute::new("sender", sender),
-        ];
-
-        b.iter().all(|item| wasm_event.attributes.contains(item))
+            .map_err(MultitestError::from)
     }
 }
 
+#[cfg(not(feature = "cosmwasm_2"))]
 pub fn sender() -> Addr {
     Addr::unchecked("sei18rszd3tmgpjvjwq2qajtmn5jqvtscd2yuygl4z")
 }
 
+#[cfg(not(feature = "cosmwasm_2"))]
 pub fn other_sender() -> Addr {
     Addr::unchecked("sei1aan9kqywf4rf274cal0hj6eyly6wu0uv7edxy2")
 }
 
+#[cfg(not(feature = "cosmwasm_2"))]
 pub fn owner() -> Addr {
     Addr::unchecked("sei1zj6fjsc2gkce878ukzg6g9wy8cl8p554dlggxd")
 }
 
+#[cfg(not(feature = "cosmwasm_2"))]
+pub fn parent() -> Addr {
+    Addr::unchecked("inj1g9v8suckezwx93zypckd4xg03r26h6ejlmsptz")
+}
+
+// The 2.x mock api only accepts addresses with its own bech32 prefix.
+#[cfg(feature = "cosmwasm_2")]
+pub fn sender() -> Addr {
+    MockApi::default().addr_make("sender")
+}
+
+#[cfg(feature = "cosmwasm_2")]
+pub fn other_sender() -> Addr {
+    MockApi::default().addr_make("other_sender")
+}
+
+#[cfg(feature = "cosmwasm_2")]
+pub fn owner() -> Addr {
+    MockApi::default().addr_make("owner")
+}
+
+#[cfg(feature = "cosmwasm_2")]
 pub fn parent() -> Addr {
+    MockApi::default().addr_make("parent")
+}
+
+pub fn remote_parent() -> Addr {
     Addr::unchecked("inj1g9v8suckezwx93zypckd4xg03r26h6ejlmsptz")
 }
 
@@ -199,9 +613,9 @@ pub fn zero_funds_instantiate_msg() -> InstantiateMsg {
 }
 
 pub fn ten_atom() -> Coin {
-    Coin::new(10, "atom")
+    coin(10, "atom")
 }
 
 pub fn zero_atom() -> Coin {
-    Coin::new(0, "atom")
+    coin(0, "atom")
 }
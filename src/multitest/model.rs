@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{coin, Addr, Coin};
+use proptest::prelude::*;
+
+use crate::msg::ExecMsg;
+
+use super::{other_sender, owner, sender, CountingSuite};
+
+pub const DENOMS: [&str; 2] = ["atom", "osmo"];
+pub const INITIAL_FUNDS: u128 = 100;
+
+pub fn initial_funds() -> Vec<Coin> {
+    DENOMS
+        .iter()
+        .map(|denom| coin(INITIAL_FUNDS, *denom))
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct Call {
+    pub sender: Addr,
+    pub msg: ExecMsg,
+    pub funds: Vec<Coin>,
+}
+
+impl Call {
+    fn new(sender: Addr, msg: ExecMsg) -> Self {
+        Self {
+            sender,
+            msg,
+            funds: vec![],
+        }
+    }
+}
+
+fn actor() -> impl Strategy<Value = Addr> {
+    prop_oneof![Just(owner()), Just(sender()), Just(other_sender())]
+}
+
+// Up to 30 of every denom, so donors run out of funds in longer sequences.
+fn funds() -> impl Strategy<Value = Vec<Coin>> {
+    proptest::collection::vec(0..=30u128, DENOMS.len()).prop_map(|amounts| {
+        DENOMS
+            .iter()
+            .zip(amounts)
+            .filter(|(_, amount)| *amount > 0)
+            .map(|(denom, amount)| coin(amount, *denom))
+            .collect()
+    })
+}
+
+pub fn call() -> impl Strategy<Value = Call> {
+    prop_oneof![
+        1 => (actor(), 0..100u64)
+            .prop_map(|(sender, value)| Call::new(sender, ExecMsg::Increment { value })),
+        1 => (actor(), 0..100u64)
+            .prop_map(|(sender, value)| Call::new(sender, ExecMsg::Reset { value })),
+        3 => (actor(), funds()).prop_map(|(sender, funds)| Call {
+            funds,
+            ..Call::new(sender, ExecMsg::Donate { campaign: None })
+        }),
+        1 => actor().prop_map(|sender| Call::new(sender, ExecMsg::Withdraw {})),
+    ]
+}
+
+// Everything the modelled calls can change: both counters and the balances of all parties.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub counter: u64,
+    pub parent_counter: u64,
+    pub balances: BTreeMap<(Addr, String), u128>,
+}
+
+impl Snapshot {
+    pub fn take(suite: &CountingSuite) -> Self {
+        let addrs = [
+            owner(),
+            sender(),
+            other_sender(),
+            suite.contract.addr(),
+            suite.parent().addr(),
+        ];
+
+        let mut balances = BTreeMap::new();
+        for addr in addrs {
+            for denom in DENOMS {
+                let balance = suite.balance(&addr, denom).unwrap();
+                balances.insert((addr.clone(), denom.to_owned()), balance.amount.u128());
+            }
+        }
+
+        Self {
+            counter: suite.value().unwrap(),
+            parent_counter: suite.parent().query_value(&suite.app).unwrap().value,
+            balances,
+        }
+    }
+
+    pub fn total(&self, denom: &str) -> u128 {
+        self.balances
+            .iter()
+            .filter(|((_, d), _)| d == denom)
+            .map(|(_, amount)| amount)
+            .sum()
+    }
+
+    fn balance(&mut self, addr: &Addr, denom: &str) -> &mut u128 {
+        self.balances
+            .get_mut(&(addr.clone(), denom.to_owned()))
+            .expect("untracked balance")
+    }
+
+    fn transfer(&mut self, from: &Addr, to: &Addr, denom: &str, amount: u128) {
+        *self.balance(from, denom) -= amount;
+        *self.balance(to, denom) += amount;
+    }
+}
+
+// Reference model of a counting contract with a non-refundable parent and no campaigns,
+// matching, grace period or IBC donations, so its whole balance is forwardable.
+pub struct Model {
+    owner: Addr,
+    contract: Addr,
+    parent: Addr,
+    minimal_donation: u128,
+    donating_period: u64,
+    donating_parent: u64,
+    part: u128,
+    pub state: Snapshot,
+}
+
+impl Model {
+    // The parent has to accept any donation, so every payout increments its counter.
+    pub fn new(
+        suite: &CountingSuite,
+        minimal_donation: u128,
+        donating_period: u64,
+        part_percent: u64,
+    ) -> Self {
+        Self {
+            owner: suite.owner.clone(),
+            contract: suite.contract.addr(),
+            parent: suite.parent().addr(),
+            minimal_donation,
+            donating_period,
+            donating_parent: donating_period,
+            part: part_percent.into(),
+            state: Snapshot::take(suite),
+        }
+    }
+
+    pub fn is_authorized(&self, call: &Call) -> bool {
+        match call.msg {
+            ExecMsg::Reset { .. } | ExecMsg::Withdraw {} => call.sender == self.owner,
+            _ => true,
+        }
+    }
+
+    // Applies the call and returns whether the contract is expected to accept it.
+    pub fn apply(&mut self, call: &Call) -> bool {
+        if !self.is_authorized(call) {
+            return false;
+        }
+
+        match &call.msg {
+            ExecMsg::Increment { value } => self.state.counter += value,
+            ExecMsg::Reset { value } => self.state.counter = *value,
+            ExecMsg::Donate { campaign: None } => return self.donate(&call.sender, &call.funds),
+            ExecMsg::Withdraw {} => {
+                for denom in DENOMS {
+                    let amount = *self.state.balance(&self.contract, denom);
+                    self.state
+                        .transfer(&self.contract, &self.owner, denom, amount);
+                }
+            }
+            msg => unreachable!("model does not cover {msg:?}"),
+        }
+
+        true
+    }
+
+    fn donate(&mut self, donor: &Addr, funds: &[Coin]) -> bool {
+        let covered = funds
+            .iter()
+            .all(|coin| *self.state.balance(donor, &coin.denom) >= coin.amount.u128());
+        if !covered {
+            return false;
+        }
+
+        for coin in funds {
+            self.state
+                .transfer(donor, &self.contract, &coin.denom, coin.amount.u128());
+        }
+
+        let accepted = self.minimal_donation == 0
+            || funds
+                .iter()
+                .any(|coin| coin.denom == DENOMS[0] && coin.amount.u128() >= self.minimal_donation);
+        if !accepted {
+            return true;
+        }
+
+        self.state.counter += 1;
+        self.donating_parent -= 1;
+
+        if self.donating_parent == 0 {
+            self.donating_parent = self.donating_period;
+
+            for denom in DENOMS {
+                let amount = *self.state.balance(&self.contract, denom) * self.part / 100;
+                self.state
+                    .transfer(&self.contract, &self.parent, denom, amount);
+            }
+            self.state.parent_counter += 1;
+        }
+
+        true
+    }
+}
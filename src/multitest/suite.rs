@@ -0,0 +1,200 @@
+use cosmwasm_std::{coin, Addr, Coin, Decimal, StdResult};
+use cw_multi_test::{App, AppResponse};
+
+use crate::msg::{InstantiateMsg, Parent};
+
+use super::{owner, CountingContract, MultitestError};
+
+const BLOCK_TIME: u64 = 5;
+
+struct SuiteParent {
+    minimal_donation: Coin,
+    donating_period: u64,
+    part: Decimal,
+}
+
+pub struct CountingSuiteBuilder {
+    balances: Vec<(Addr, Vec<Coin>)>,
+    owner: Addr,
+    label: String,
+    counter: u64,
+    minimal_donation: Coin,
+    funds: Vec<Coin>,
+    admin: Option<String>,
+    parents: Vec<SuiteParent>,
+}
+
+impl Default for CountingSuiteBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CountingSuiteBuilder {
+    pub fn new() -> Self {
+        Self {
+            balances: vec![],
+            owner: owner(),
+            label: "counting-contract".to_owned(),
+            counter: 0,
+            minimal_donation: coin(0, "atom"),
+            funds: vec![],
+            admin: None,
+            parents: vec![],
+        }
+    }
+
+    pub fn with_balance(mut self, addr: &Addr, funds: &[Coin]) -> Self {
+        self.balances.push((addr.clone(), funds.to_vec()));
+        self
+    }
+
+    pub fn with_owner(mut self, owner: &Addr) -> Self {
+        self.owner = owner.clone();
+        self
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = label.to_owned();
+        self
+    }
+
+    pub fn with_counter(mut self, counter: u64) -> Self {
+        self.counter = counter;
+        self
+    }
+
+    pub fn with_minimal_donation(mut self, minimal_donation: Coin) -> Self {
+        self.minimal_donation = minimal_donation;
+        self
+    }
+
+    // Sent by the owner when instantiating the contract, so the owner needs the balance.
+    pub fn with_funds(mut self, funds: &[Coin]) -> Self {
+        self.funds = funds.to_vec();
+        self
+    }
+
+    pub fn with_admin(mut self, admin: &Addr) -> Self {
+        self.admin = Some(admin.to_string());
+        self
+    }
+
+    // Every call adds a parent above the previous one, the first is the contract's direct parent.
+    pub fn with_parent(
+        mut self,
+        minimal_donation: Coin,
+        donating_period: u64,
+        part: Decimal,
+    ) -> Self {
+        self.parents.push(SuiteParent {
+            minimal_donation,
+            donating_period,
+            part,
+        });
+        self
+    }
+
+    #[track_caller]
+    pub fn build(self) -> Result<CountingSuite, MultitestError> {
+        let balances = self.balances;
+        let mut app = App::new(|router, _api, storage| {
+            for (addr, funds) in balances {
+                router.bank.init_balance(storage, &addr, funds).unwrap();
+            }
+        });
+
+        let code_id = CountingContract::store_code(&mut app);
+
+        let mut parents = vec![];
+        let mut parent: Option<Parent> = None;
+        for config in self.parents.into_iter().rev() {
+            let contract = CountingContract::instantiate_with_msg(
+                &mut app,
+                code_id,
+                self.owner.clone(),
+                "Parent Contract",
+                &InstantiateMsg::new(0, config.minimal_donation, parent),
+                &[],
+                None,
+            )?;
+
+            parent = Some(Parent {
+                addr: contract.addr().to_string(),
+                donating_period: config.donating_period,
+                part: config.part,
+                remote: None,
+            });
+            parents.insert(0, contract);
+        }
+
+        let contract = CountingContract::instantiate_with_msg(
+            &mut app,
+            code_id,
+            self.owner.clone(),
+            &self.label,
+            &InstantiateMsg::new(self.counter, self.minimal_donation, parent),
+            &self.funds,
+            self.admin,
+        )?;
+
+        Ok(CountingSuite {
+            app,
+            code_id,
+            owner: self.owner,
+            contract,
+            parents,
+        })
+    }
+}
+
+pub struct CountingSuite {
+    pub app: App,
+    pub code_id: u64,
+    pub owner: Addr,
+    pub contract: CountingContract,
+    // Direct parent first.
+    pub parents: Vec<CountingContract>,
+}
+
+impl CountingSuite {
+    #[track_caller]
+    pub fn parent(&self) -> &CountingContract {
+        self.parents.first().expect("suite built without a parent")
+    }
+
+    #[track_caller]
+    pub fn donate(&mut self, sender: &Addr, funds: &[Coin]) -> Result<AppResponse, MultitestError> {
+        self.contract.donate(&mut self.app, sender.clone(), funds)
+    }
+
+    #[track_caller]
+    pub fn donate_times(
+        &mut self,
+        sender: &Addr,
+        times: usize,
+        funds: &[Coin],
+    ) -> Result<Vec<AppResponse>, MultitestError> {
+        (0..times).map(|_| self.donate(sender, funds)).collect()
+    }
+
+    pub fn advance_blocks(&mut self, blocks: u64) {
+        self.app.update_block(|block| {
+            block.height += blocks;
+            block.time = block.time.plus_seconds(blocks * BLOCK_TIME);
+        });
+    }
+
+    pub fn advance_time(&mut self, seconds: u64) {
+        self.app
+            .update_block(|block| block.time = block.time.plus_seconds(seconds));
+    }
+
+    pub fn value(&self) -> StdResult<u64> {
+        self.contract.query_value(&self.app).map(|resp| resp.value)
+    }
+
+    pub fn balance(&self, addr: &Addr, denom: &str) -> StdResult<Coin> {
+        self.app.wrap().query_balance(addr, denom)
+    }
+}
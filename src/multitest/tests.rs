@@ -1,19 +1,58 @@
+// Balances are still checked with all-balances queries, which 2.x deprecates.
+#![cfg_attr(feature = "cosmwasm_2", allow(deprecated))]
+
 use std::vec;
 
-use cosmwasm_std::{coins, to_binary, Coin, Decimal};
+use cosmwasm_std::{
+    coin, coins, from_json,
+    testing::{
+        mock_dependencies, mock_env, mock_ibc_channel_close_init, mock_ibc_channel_connect_ack,
+        mock_ibc_channel_open_init, mock_ibc_packet_ack, mock_ibc_packet_recv,
+        mock_ibc_packet_timeout, MockApi, MockQuerier, MockStorage,
+    },
+    to_json_binary, BankMsg, Coin, CosmosMsg, Decimal, IbcAcknowledgement, IbcMsg, IbcOrder,
+    IbcTimeout, MessageInfo, OwnedDeps, ReplyOn, Response, StdError, SubMsg, Uint128,
+};
+#[cfg(feature = "cosmwasm_2")]
+use cosmwasm_std::{
+    to_json_string, Binary, IbcAckCallbackMsg, IbcCallbackRequest, IbcSourceCallbackMsg,
+    IbcSrcCallback, IbcTimeoutCallbackMsg, MsgResponse, Reply, SubMsgResponse, SubMsgResult,
+};
+use cw20::Cw20Coin;
 use cw_multi_test::App;
+use cw_storage_plus::Item;
+use proptest::{collection::vec as prop_vec, prelude::*};
+use serde::Serialize;
 
+#[cfg(not(feature = "cosmwasm_2"))]
+use crate::state::{ParentDonation, PARENT_DONATION};
 use crate::{
-    msg::{IncrementResp, ValueResp},
-    state::{ParentDonation, State, PARENT_DONATION, STATE},
+    error::ContractError,
+    helpers::CountingContractHelper,
+    ibc_channel_close, ibc_channel_connect, ibc_channel_open, ibc_packet_ack, ibc_packet_receive,
+    ibc_packet_timeout,
+    migration::{migrate_0_1_0, migrate_0_2_0, migration_path, parse_version, MIGRATIONS},
+    msg::{
+        AllowedPrefix, CampaignStatus, IbcAck, IbcChannelsResp, IbcPacketMsg, Ics20Packet,
+        IncrementResp, MigrationPreviewResp, MigrationStepResp, PendingWithdrawalDelayResp,
+        PendingWithdrawalResp, ReceiptMetadata, RemoteParent, StateOverride, StoredState,
+        ValueResp, WithdrawalDelay, WithdrawalDelayResp,
+    },
+    state::{State, STATE},
 };
+#[cfg(feature = "cosmwasm_2")]
+use crate::{ibc_source_callback, msg::ParentTransfersResp, reply};
+#[cfg(not(feature = "cosmwasm_2"))]
 use counting_contract_0_1::multitest::CountingContract as CountingContract_0_1;
+use receipt_nft::{NftInfoResponse, OwnerOfResponse};
 
+use super::model::{self, Model, Snapshot};
 use super::*;
 
 const COUNTING_LABEL: &str = "counting-contract";
 const EMPTY_FUNDS: &[Coin] = &[];
 const ATOM: &str = "atom";
+const OSMO: &str = "osmo";
 
 #[test]
 fn query_value_should_work() {
@@ -53,19 +92,44 @@ fn increment_should_work() {
 
     let expected_value = 10;
     let data = IncrementResp::new(expected_value);
-    assert_eq!(resp.data.unwrap(), to_binary(&data).unwrap());
+    assert_eq!(resp.data.unwrap(), to_json_binary(&data).unwrap());
 
-    assert!(CountingContract::verify_events(
-        resp.events,
-        "increment",
-        sender().as_str()
-    ));
+    assert_events(&resp.events).contains(
+        EventMatcher::wasm()
+            .contract(&contract.addr())
+            .action("increment")
+            .attr("sender", sender()),
+    );
 
     let resp: ValueResp = contract.query_value(&app).unwrap();
 
     assert_eq!(resp.value, 10);
 }
 
+#[test]
+#[should_panic(expected = "- counter=\"11\"\n      + counter=\"10\"")]
+fn increment_events_mismatch_should_report_diff() {
+    let mut app = App::default();
+    let contract_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        sender(),
+        COUNTING_LABEL,
+        0,
+        ten_atom(),
+    )
+    .unwrap();
+
+    let resp = contract.increment(&mut app, sender(), 10).unwrap();
+
+    assert_events(&resp.events).contains(
+        EventMatcher::wasm()
+            .action("increment")
+            .attr("counter", "11"),
+    );
+}
+
 #[test]
 fn reset_should_work() {
     let mut app = App::default();
@@ -82,7 +146,10 @@ fn reset_should_work() {
 
     let resp = contract.reset(&mut app, sender(), 10).unwrap();
 
-    assert_eq!(resp.data, Some(to_binary(&IncrementResp::new(10)).unwrap()));
+    assert_eq!(
+        resp.data,
+        Some(to_json_binary(&IncrementResp::new(10)).unwrap())
+    );
 
     let resp = contract.query_value(&app).unwrap();
 
@@ -128,11 +195,13 @@ fn donate_should_work() {
 
     let resp = contract.donate(&mut app, sender(), EMPTY_FUNDS).unwrap();
 
-    assert!(CountingContract::verify_events(
-        resp.events,
-        "donate",
-        sender().as_str()
-    ));
+    assert_events(&resp.events).contains(
+        EventMatcher::wasm()
+            .contract(&contract.addr())
+            .action("donate")
+            .attr("sender", sender())
+            .lacks_key("donation_id"),
+    );
 
     let resp: ValueResp = contract.query_value(&app).unwrap();
 
@@ -155,11 +224,12 @@ fn donate_expecting_no_funds_should_work() {
 
     let resp = contract.donate(&mut app, sender(), &[]).unwrap();
 
-    assert!(CountingContract::verify_events(
-        resp.events,
-        "donate",
-        sender().as_str()
-    ));
+    assert_events(&resp.events).contains(
+        EventMatcher::wasm()
+            .contract(&contract.addr())
+            .action("donate")
+            .attr("sender", sender()),
+    );
 
     let resp = contract.query_value(&app).unwrap();
 
@@ -168,49 +238,38 @@ fn donate_expecting_no_funds_should_work() {
 
 #[test]
 fn donate_with_funds_should_work() {
-    let mut app = App::new(|router, _api, storage| {
-        router
-            .bank
-            .init_balance(storage, &sender(), coins(100, ATOM))
-            .unwrap();
-    });
-
-    let contract_id = CountingContract::store_code(&mut app);
-    let contract = CountingContract::instantiate_with_funds_admin(
-        &mut app,
-        contract_id,
-        sender(),
-        COUNTING_LABEL,
-        0,
-        zero_atom(),
-        vec![ten_atom()].as_slice(),
-        None,
-        None,
-    )
-    .unwrap();
+    let CountingSuite {
+        mut app, contract, ..
+    } = CountingSuiteBuilder::new()
+        .with_balance(&sender(), &coins(100, ATOM))
+        .with_owner(&sender())
+        .with_funds(&[ten_atom()])
+        .build()
+        .unwrap();
 
     let sender_balance = CountingContract::query_balance(&app, sender(), ATOM).unwrap();
-    assert_eq!(sender_balance, Coin::new(90, ATOM));
+    assert_eq!(sender_balance, coin(90, ATOM));
 
     let contract_balance = CountingContract::query_balance(&app, contract.addr(), ATOM).unwrap();
 
-    assert_eq!(contract_balance, Coin::new(10, ATOM));
+    assert_eq!(contract_balance, coin(10, ATOM));
 
     let resp = contract
         .donate(&mut app, sender(), vec![ten_atom()].as_slice())
         .unwrap();
 
     let balance = app.wrap().query_balance(sender(), ATOM).unwrap();
-    assert_eq!(balance, Coin::new(80, ATOM));
+    assert_eq!(balance, coin(80, ATOM));
 
     let contract_balance = CountingContract::query_balance(&app, contract.addr(), ATOM).unwrap();
-    assert_eq!(contract_balance, Coin::new(20, ATOM));
+    assert_eq!(contract_balance, coin(20, ATOM));
 
-    assert!(CountingContract::verify_events(
-        resp.events,
-        "donate",
-        sender().as_str()
-    ));
+    assert_events(&resp.events).contains(
+        EventMatcher::wasm()
+            .contract(&contract.addr())
+            .action("donate")
+            .attr("sender", sender()),
+    );
 
     let resp = contract.query_value(&app).unwrap();
 
@@ -218,35 +277,97 @@ fn donate_with_funds_should_work() {
 }
 
 #[test]
-fn withdraw_should_work() {
+fn donate_insufficient_funds_should_report_bank_error() {
+    let mut suite = CountingSuiteBuilder::new()
+        .with_balance(&sender(), &coins(5, ATOM))
+        .build()
+        .unwrap();
+
+    let err = suite.donate(&sender(), &coins(10, ATOM)).unwrap_err();
+    assert!(
+        matches!(err, MultitestError::Bank(StdError::Overflow { .. })),
+        "{err}"
+    );
+    assert_eq!(err.contract(), None);
+    assert_eq!(suite.value().unwrap(), 0);
+
+    let err = CountingContract::instantiate(
+        &mut suite.app,
+        suite.code_id + 1,
+        owner(),
+        COUNTING_LABEL,
+        0,
+        zero_atom(),
+    )
+    .unwrap_err();
+    assert!(
+        matches!(&err, MultitestError::Unknown(msg) if msg.contains("unregistered code id")),
+        "{err}"
+    );
+}
+
+#[test]
+fn helper_messages_should_work() {
     let mut app = App::new(|router, _api, storage| {
         router
             .bank
             .init_balance(storage, &sender(), coins(100, ATOM))
             .unwrap();
-
-        router
-            .bank
-            .init_balance(storage, &other_sender(), vec![ten_atom()])
-            .unwrap();
     });
 
     let contract_id = CountingContract::store_code(&mut app);
-    let contract = CountingContract::instantiate_with_funds_admin(
+    let contract = CountingContract::instantiate(
         &mut app,
         contract_id,
         owner(),
         COUNTING_LABEL,
         0,
-        zero_atom(),
-        &[],
-        None,
-        None,
+        ten_atom(),
+    )
+    .unwrap();
+
+    let helper = CountingContractHelper(contract.addr());
+
+    app.execute(sender(), helper.increment(5).unwrap()).unwrap();
+    app.execute(sender(), helper.donate(coins(10, ATOM)).unwrap())
+        .unwrap();
+    assert_eq!(helper.query_value(&app.wrap()).unwrap().value, 6);
+
+    let err = app
+        .execute(sender(), helper.reset(0).unwrap())
+        .unwrap_err()
+        .downcast::<ContractError>()
+        .unwrap();
+    assert_eq!(
+        ContractError::UnauthorizedErr {
+            owner: owner().to_string()
+        },
+        err
+    );
+
+    app.execute(
+        owner(),
+        helper.withdraw_to(parent(), vec![], vec![]).unwrap(),
     )
     .unwrap();
+    assert_eq!(
+        app.wrap().query_balance(parent(), ATOM).unwrap(),
+        coin(10, ATOM)
+    );
+}
+
+#[test]
+fn withdraw_should_work() {
+    let CountingSuite {
+        mut app, contract, ..
+    } = CountingSuiteBuilder::new()
+        .with_balance(&sender(), &coins(100, ATOM))
+        .with_balance(&other_sender(), &[ten_atom()])
+        .build()
+        .unwrap();
 
     let contract_balance = CountingContract::query_balance(&app, contract.addr(), ATOM).unwrap();
-    assert_eq!(contract_balance, Coin::new(0, ATOM));
+    assert_eq!(contract_balance, coin(0, ATOM));
 
     contract
         .donate(&mut app, sender(), vec![ten_atom()].as_slice())
@@ -256,37 +377,25 @@ fn withdraw_should_work() {
         .unwrap();
 
     let contract_balance = CountingContract::query_balance(&app, contract.addr(), ATOM).unwrap();
-    assert_eq!(contract_balance, Coin::new(20, ATOM));
+    assert_eq!(contract_balance, coin(20, ATOM));
 
     contract.withdraw(&mut app, owner()).unwrap();
 
     let sender_balance = CountingContract::query_balance(&app, sender(), ATOM).unwrap();
-    assert_eq!(sender_balance, Coin::new(90, ATOM));
+    assert_eq!(sender_balance, coin(90, ATOM));
 
     let other_balance = CountingContract::query_balance(&app, other_sender(), ATOM).unwrap();
-    assert_eq!(other_balance, Coin::new(0, ATOM));
+    assert_eq!(other_balance, coin(0, ATOM));
 
     let owner_balance = CountingContract::query_balance(&app, owner(), ATOM).unwrap();
-    assert_eq!(owner_balance, Coin::new(20, ATOM));
+    assert_eq!(owner_balance, coin(20, ATOM));
 }
 
 #[test]
 fn withdraw_not_owner_should_fail() {
-    let mut app = App::default();
-
-    let contract_id = CountingContract::store_code(&mut app);
-    let contract = CountingContract::instantiate_with_funds_admin(
-        &mut app,
-        contract_id,
-        owner(),
-        COUNTING_LABEL,
-        0,
-        zero_atom(),
-        &[],
-        None,
-        None,
-    )
-    .unwrap();
+    let CountingSuite {
+        mut app, contract, ..
+    } = CountingSuiteBuilder::new().build().unwrap();
 
     let err = contract.withdraw(&mut app, other_sender()).unwrap_err();
 
@@ -300,34 +409,16 @@ fn withdraw_not_owner_should_fail() {
 
 #[test]
 fn withdraw_to_should_work() {
-    let mut app = App::new(|router, _api, storage| {
-        router
-            .bank
-            .init_balance(storage, &sender(), coins(100, ATOM))
-            .unwrap();
-
-        router
-            .bank
-            .init_balance(storage, &other_sender(), vec![ten_atom()])
-            .unwrap();
-    });
-
-    let contract_id = CountingContract::store_code(&mut app);
-    let contract = CountingContract::instantiate_with_funds_admin(
-        &mut app,
-        contract_id,
-        owner(),
-        COUNTING_LABEL,
-        0,
-        zero_atom(),
-        &[],
-        None,
-        None,
-    )
-    .unwrap();
+    let CountingSuite {
+        mut app, contract, ..
+    } = CountingSuiteBuilder::new()
+        .with_balance(&sender(), &coins(100, ATOM))
+        .with_balance(&other_sender(), &[ten_atom()])
+        .build()
+        .unwrap();
 
     let contract_balance = CountingContract::query_balance(&app, contract.addr(), ATOM).unwrap();
-    assert_eq!(contract_balance, Coin::new(0, ATOM));
+    assert_eq!(contract_balance, coin(0, ATOM));
 
     contract
         .donate(&mut app, sender(), vec![ten_atom()].as_slice())
@@ -337,7 +428,7 @@ fn withdraw_to_should_work() {
         .unwrap();
 
     let contract_balance = CountingContract::query_balance(&app, contract.addr(), ATOM).unwrap();
-    assert_eq!(contract_balance, Coin::new(20, ATOM));
+    assert_eq!(contract_balance, coin(20, ATOM));
 
     let send_funds = coins(10, ATOM);
     contract
@@ -345,29 +436,17 @@ fn withdraw_to_should_work() {
         .unwrap();
 
     let sender_balance = app.wrap().query_balance(sender(), ATOM).unwrap();
-    assert_eq!(sender_balance, Coin::new(90, ATOM));
+    assert_eq!(sender_balance, coin(90, ATOM));
 
     let other_balance = app.wrap().query_balance(other_sender(), ATOM).unwrap();
-    assert_eq!(other_balance, Coin::new(10, ATOM));
+    assert_eq!(other_balance, coin(10, ATOM));
 }
 
 #[test]
 fn withdraw_to_not_owner_should_fail() {
-    let mut app = App::default();
-
-    let contract_id = CountingContract::store_code(&mut app);
-    let contract = CountingContract::instantiate_with_funds_admin(
-        &mut app,
-        contract_id,
-        owner(),
-        COUNTING_LABEL,
-        0,
-        zero_atom(),
-        &[],
-        None,
-        None,
-    )
-    .unwrap();
+    let CountingSuite {
+        mut app, contract, ..
+    } = CountingSuiteBuilder::new().build().unwrap();
 
     let send_funds = coins(10, ATOM);
     let err = contract
@@ -384,21 +463,9 @@ fn withdraw_to_not_owner_should_fail() {
 
 #[test]
 fn withdraw_to_invalid_address_should_fail() {
-    let mut app = App::default();
-
-    let contract_id = CountingContract::store_code(&mut app);
-    let contract = CountingContract::instantiate_with_funds_admin(
-        &mut app,
-        contract_id,
-        owner(),
-        COUNTING_LABEL,
-        0,
-        zero_atom(),
-        &[],
-        None,
-        None,
-    )
-    .unwrap();
+    let CountingSuite {
+        mut app, contract, ..
+    } = CountingSuiteBuilder::new().build().unwrap();
 
     let send_funds = coins(10, ATOM);
     let err = contract
@@ -413,152 +480,3339 @@ fn withdraw_to_invalid_address_should_fail() {
     )
 }
 
-#[test]
-fn migrate_should_work() {
-    let mut app = App::new(|router, _api, storage| {
-        router
-            .bank
-            .init_balance(storage, &sender(), coins(10, ATOM))
-            .unwrap();
-    });
+fn withdraw_to_setup() -> (App, CountingContract) {
+    let CountingSuite {
+        mut app, contract, ..
+    } = CountingSuiteBuilder::new()
+        .with_balance(&sender(), &[coin(10, ATOM), coin(5, OSMO)])
+        .build()
+        .unwrap();
 
-    let old_code_id = CountingContract_0_1::store_code(&mut app);
-    let new_code_id = CountingContract::store_code(&mut app);
+    contract
+        .donate(&mut app, sender(), &[coin(10, ATOM), coin(5, OSMO)])
+        .unwrap();
 
-    let contract = CountingContract_0_1::instantiate_with_funds(
-        &mut app,
-        old_code_id,
-        owner(),
-        COUNTING_LABEL,
-        0,
-        zero_atom(),
-        &[],
-        other_sender(),
-    )
-    .unwrap();
+    (app, contract)
+}
+
+#[test]
+fn withdraw_to_empty_funds_should_withdraw_all() {
+    let (mut app, contract) = withdraw_to_setup();
 
     contract
-        .donate(&mut app, sender(), &coins(10, ATOM))
+        .withdraw_to(&mut app, owner(), other_sender().to_string(), vec![])
         .unwrap();
 
-    let contract =
-        CountingContract::migrate(&mut app, contract.addr(), new_code_id, other_sender(), None)
-            .unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(other_sender()).unwrap(),
+        vec![coin(10, ATOM), coin(5, OSMO)]
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![]
+    );
+}
 
-    let resp = contract.query_value(&app).unwrap();
-    assert_eq!(resp.value, 1);
+#[test]
+fn withdraw_to_partial_should_skip_unrequested_denoms() {
+    let (mut app, contract) = withdraw_to_setup();
 
-    let state = STATE.query(&app.wrap(), contract.addr()).unwrap();
+    contract
+        .withdraw_to(
+            &mut app,
+            owner(),
+            other_sender().to_string(),
+            coins(4, ATOM),
+        )
+        .unwrap();
 
-    assert_eq!(state, State::new(1, zero_atom(), owner(), None))
+    assert_eq!(
+        app.wrap().query_all_balances(other_sender()).unwrap(),
+        coins(4, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![coin(6, ATOM), coin(5, OSMO)]
+    );
 }
 
 #[test]
-fn migrate_no_update_should_works() {
-    let mut app = App::new(|router, _api, storage| {
-        router
-            .bank
-            .init_balance(storage, &sender(), coins(10, ATOM))
-            .unwrap();
-    });
-
-    let code_id = CountingContract::store_code(&mut app);
-    let contract = CountingContract::instantiate_with_funds_admin(
-        &mut app,
-        code_id,
-        sender(),
-        COUNTING_LABEL,
-        0,
-        ten_atom(),
-        &[],
-        Some(owner().to_string()),
-        None,
-    )
-    .unwrap();
+fn withdraw_to_over_requested_should_be_capped() {
+    let (mut app, contract) = withdraw_to_setup();
 
     contract
-        .donate(&mut app, sender(), vec![ten_atom()].as_slice())
+        .withdraw_to(
+            &mut app,
+            owner(),
+            other_sender().to_string(),
+            vec![coin(100, ATOM), coin(3, OSMO)],
+        )
         .unwrap();
 
-    let contract =
-        CountingContract::migrate(&mut app, contract.addr(), code_id, owner(), None).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(other_sender()).unwrap(),
+        vec![coin(10, ATOM), coin(3, OSMO)]
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(2, OSMO)
+    );
+}
 
-    let resp = contract.query_value(&app).unwrap();
-    assert_eq!(resp.value, 1);
+#[test]
+fn withdraw_to_unavailable_denoms_should_fail() {
+    let (mut app, contract) = withdraw_to_setup();
 
-    let state = STATE.query(&app.wrap(), contract.addr()).unwrap();
-    assert_eq!(state, State::new(1, ten_atom(), sender(), None))
+    let err = contract
+        .withdraw_to(
+            &mut app,
+            owner(),
+            other_sender().to_string(),
+            vec![coin(10, ATOM), coin(10, "btc")],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        ContractError::UnavailableDenomsErr {
+            denoms: vec!["btc".into()]
+        },
+        err,
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(other_sender()).unwrap(),
+        vec![]
+    );
 }
 
 #[test]
-fn donate_parent_should_works() {
-    let mut app = App::new(|router, _api, storage| {
-        router
-            .bank
-            .init_balance(storage, &sender(), coins(20, ATOM))
-            .unwrap();
+fn withdraw_to_empty_contract_should_send_nothing() {
+    let CountingSuite {
+        mut app, contract, ..
+    } = CountingSuiteBuilder::new().build().unwrap();
+
+    contract
+        .withdraw_to(&mut app, owner(), other_sender().to_string(), vec![])
+        .unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(other_sender()).unwrap(),
+        vec![]
+    );
+}
+
+#[test]
+fn withdraw_split_should_work() {
+    let (mut app, contract) = withdraw_to_setup();
+
+    let resp = contract
+        .withdraw_split(
+            &mut app,
+            owner(),
+            vec![
+                (parent().to_string(), Decimal::percent(50)),
+                (other_sender().to_string(), Decimal::percent(30)),
+                (owner().to_string(), Decimal::percent(20)),
+            ],
+            vec![],
+        )
+        .unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(parent()).unwrap(),
+        vec![coin(5, ATOM), coin(2, OSMO)]
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(other_sender()).unwrap(),
+        vec![coin(3, ATOM), coin(1, OSMO)]
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(owner()).unwrap(),
+        vec![coin(2, ATOM), coin(2, OSMO)]
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![]
+    );
+
+    let split = |recipient: Addr, amount: &str| {
+        EventMatcher::custom("withdraw_split")
+            .contract(&contract.addr())
+            .attr("recipient", recipient)
+            .attr("amount", amount)
+    };
+    assert_events(&resp.events)
+        .count(EventMatcher::custom("withdraw_split"), 3)
+        .in_order([
+            split(parent(), "5atom,2osmo"),
+            split(other_sender(), "3atom,1osmo"),
+            split(owner(), "2atom,2osmo"),
+        ]);
+}
+
+#[test]
+fn withdraw_split_by_weights_should_work() {
+    let (mut app, contract) = withdraw_to_setup();
+
+    contract
+        .withdraw_split(
+            &mut app,
+            owner(),
+            vec![
+                (parent().to_string(), Decimal::one()),
+                (other_sender().to_string(), Decimal::one()),
+                (owner().to_string(), Decimal::one()),
+            ],
+            vec![],
+        )
+        .unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(parent()).unwrap(),
+        vec![coin(3, ATOM), coin(1, OSMO)]
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(other_sender()).unwrap(),
+        vec![coin(3, ATOM), coin(1, OSMO)]
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(owner()).unwrap(),
+        vec![coin(4, ATOM), coin(3, OSMO)]
+    );
+}
+
+#[test]
+fn withdraw_split_capped_should_work() {
+    let (mut app, contract) = withdraw_to_setup();
+
+    contract
+        .withdraw_split(
+            &mut app,
+            owner(),
+            vec![
+                (parent().to_string(), Decimal::percent(50)),
+                (other_sender().to_string(), Decimal::percent(50)),
+            ],
+            coins(5, ATOM),
+        )
+        .unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(parent()).unwrap(),
+        coins(2, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(other_sender()).unwrap(),
+        coins(3, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![coin(5, ATOM), coin(5, OSMO)]
+    );
+}
+
+#[test]
+fn withdraw_split_should_fail() {
+    let (mut app, contract) = withdraw_to_setup();
+
+    let err = contract
+        .withdraw_split(
+            &mut app,
+            owner(),
+            vec![
+                (parent().to_string(), Decimal::zero()),
+                (other_sender().to_string(), Decimal::zero()),
+            ],
+            vec![],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidSharesErr {
+            total: Decimal::zero()
+        },
+        err
+    );
+
+    let err = contract
+        .withdraw_split(
+            &mut app,
+            owner(),
+            vec![
+                (parent().to_string(), Decimal::percent(50)),
+                ("ABC".into(), Decimal::percent(50)),
+            ],
+            vec![],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidAddressErr {
+            address: "ABC".into()
+        },
+        err
+    );
+
+    let err = contract
+        .withdraw_split(
+            &mut app,
+            other_sender(),
+            vec![(parent().to_string(), Decimal::one())],
+            vec![],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnauthorizedErr {
+            owner: owner().to_string()
+        },
+        err
+    );
+}
+
+fn time_locked_setup() -> (App, CountingContract) {
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender(), coins(10, ATOM))
+            .unwrap();
     });
 
-    let code_id = CountingContract::store_code(&mut app);
-    let parent_contract = CountingContract::instantiate_with_funds_admin(
+    let contract_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate_with_msg(
         &mut app,
-        code_id,
+        contract_id,
         owner(),
-        "Parent Contract",
-        0,
-        ten_atom(),
+        COUNTING_LABEL,
+        &InstantiateMsg {
+            withdrawal_delay: Some(WithdrawalDelay {
+                delay: 100,
+                guardian: Some(other_sender().to_string()),
+            }),
+            ..zero_funds_instantiate_msg()
+        },
         &[],
         None,
-        None,
     )
     .unwrap();
 
-    let contract = CountingContract::instantiate_with_funds_admin(
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+
+    (app, contract)
+}
+
+#[test]
+fn time_locked_withdrawal_should_work() {
+    let (mut app, contract) = time_locked_setup();
+
+    let err = contract.withdraw(&mut app, owner()).unwrap_err();
+    assert_eq!(ContractError::WithdrawalTimeLockedErr {}, err);
+
+    let err = contract
+        .withdraw_to(&mut app, owner(), parent().to_string(), vec![])
+        .unwrap_err();
+    assert_eq!(ContractError::WithdrawalTimeLockedErr {}, err);
+
+    let start_time = app.block_info().time;
+    let err = contract
+        .create_stream(
+            &mut app,
+            owner(),
+            parent().to_string(),
+            coin(2, ATOM),
+            10,
+            start_time,
+            start_time.plus_seconds(50),
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::WithdrawalTimeLockedErr {}, err);
+
+    contract
+        .request_withdrawal(
+            &mut app,
+            owner(),
+            parent().to_string(),
+            coins(6, ATOM),
+            vec![],
+        )
+        .unwrap();
+
+    let release_at = app.block_info().time.plus_seconds(100);
+    let resp = contract.query_pending_withdrawals(&app).unwrap();
+    assert_eq!(
+        resp.withdrawals,
+        vec![PendingWithdrawalResp {
+            id: 0,
+            receiver: parent(),
+            funds: coins(6, ATOM),
+            cw20_funds: vec![],
+            release_at,
+        }]
+    );
+
+    let err = contract
+        .execute_withdrawal(&mut app, sender(), 0)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::WithdrawalNotMaturedErr { id: 0, release_at },
+        err
+    );
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    contract.execute_withdrawal(&mut app, sender(), 0).unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(parent()).unwrap(),
+        coins(6, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(4, ATOM)
+    );
+
+    let resp = contract.query_pending_withdrawals(&app).unwrap();
+    assert_eq!(resp.withdrawals, vec![]);
+
+    let err = contract
+        .execute_withdrawal(&mut app, sender(), 0)
+        .unwrap_err();
+    assert_eq!(ContractError::WithdrawalNotFoundErr { id: 0 }, err);
+}
+
+#[test]
+fn cancel_withdrawal_should_work() {
+    let (mut app, contract) = time_locked_setup();
+
+    contract
+        .request_withdrawal(&mut app, owner(), parent().to_string(), vec![], vec![])
+        .unwrap();
+    contract
+        .request_withdrawal(&mut app, owner(), parent().to_string(), vec![], vec![])
+        .unwrap();
+
+    let err = contract
+        .cancel_withdrawal(&mut app, sender(), 0)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnauthorizedErr {
+            owner: owner().to_string()
+        },
+        err
+    );
+
+    contract
+        .cancel_withdrawal(&mut app, other_sender(), 0)
+        .unwrap();
+
+    let err = contract.query_pending_withdrawal(&app, 0).unwrap_err();
+    assert!(err.to_string().contains("not found"));
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    let err = contract
+        .cancel_withdrawal(&mut app, owner(), 1)
+        .unwrap_err();
+    assert_eq!(ContractError::WithdrawalMaturedErr { id: 1 }, err);
+
+    contract.execute_withdrawal(&mut app, owner(), 1).unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(parent()).unwrap(),
+        coins(10, ATOM)
+    );
+}
+
+#[test]
+fn set_withdrawal_delay_should_lock_withdrawals() {
+    let (mut app, contract) = withdraw_to_setup();
+
+    let err = contract
+        .set_withdrawal_delay(&mut app, sender(), 100, None)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnauthorizedErr {
+            owner: owner().to_string()
+        },
+        err
+    );
+
+    contract
+        .set_withdrawal_delay(&mut app, owner(), 100, other_sender().to_string())
+        .unwrap();
+    assert_eq!(
+        contract.query_withdrawal_delay(&app).unwrap(),
+        WithdrawalDelayResp {
+            delay: 100,
+            guardian: Some(other_sender()),
+            pending: None,
+        }
+    );
+
+    let err = contract.withdraw(&mut app, owner()).unwrap_err();
+    assert_eq!(ContractError::WithdrawalTimeLockedErr {}, err);
+
+    contract
+        .request_withdrawal(&mut app, owner(), owner().to_string(), vec![], vec![])
+        .unwrap();
+    contract
+        .cancel_withdrawal(&mut app, other_sender(), 0)
+        .unwrap();
+
+    // A longer delay isn't a weaker time lock, it applies right away.
+    contract
+        .set_withdrawal_delay(&mut app, owner(), 200, other_sender().to_string())
+        .unwrap();
+    assert_eq!(contract.query_withdrawal_delay(&app).unwrap().delay, 200);
+}
+
+#[test]
+fn weakened_withdrawal_delay_should_wait_for_current_delay() {
+    let (mut app, contract) = time_locked_setup();
+
+    contract
+        .set_withdrawal_delay(&mut app, owner(), 0, None)
+        .unwrap();
+
+    let effective_at = app.block_info().time.plus_seconds(100);
+    assert_eq!(
+        contract.query_withdrawal_delay(&app).unwrap(),
+        WithdrawalDelayResp {
+            delay: 100,
+            guardian: Some(other_sender()),
+            pending: Some(PendingWithdrawalDelayResp {
+                delay: 0,
+                guardian: None,
+                effective_at,
+            }),
+        }
+    );
+
+    let err = contract.withdraw(&mut app, owner()).unwrap_err();
+    assert_eq!(ContractError::WithdrawalTimeLockedErr {}, err);
+
+    let err = contract
+        .cancel_withdrawal_delay_change(&mut app, sender())
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnauthorizedErr {
+            owner: owner().to_string()
+        },
+        err
+    );
+
+    contract
+        .cancel_withdrawal_delay_change(&mut app, other_sender())
+        .unwrap();
+    assert_eq!(contract.query_withdrawal_delay(&app).unwrap().pending, None);
+
+    // Replacing the guardian is as much of a weakening as shortening the delay.
+    contract
+        .set_withdrawal_delay(&mut app, owner(), 100, sender().to_string())
+        .unwrap();
+    contract
+        .request_withdrawal(&mut app, owner(), owner().to_string(), vec![], vec![])
+        .unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(50));
+
+    let err = contract
+        .cancel_withdrawal(&mut app, sender(), 0)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnauthorizedErr {
+            owner: owner().to_string()
+        },
+        err
+    );
+    contract
+        .cancel_withdrawal(&mut app, other_sender(), 0)
+        .unwrap();
+
+    contract
+        .set_withdrawal_delay(&mut app, owner(), 0, None)
+        .unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    assert_eq!(
+        contract.query_withdrawal_delay(&app).unwrap(),
+        WithdrawalDelayResp {
+            delay: 0,
+            guardian: None,
+            pending: None,
+        }
+    );
+
+    let err = contract
+        .cancel_withdrawal_delay_change(&mut app, owner())
+        .unwrap_err();
+    assert_eq!(ContractError::NoPendingWithdrawalDelayErr {}, err);
+
+    contract.withdraw(&mut app, owner()).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(owner()).unwrap(),
+        coins(10, ATOM)
+    );
+}
+
+#[test]
+fn request_withdrawal_not_owner_should_fail() {
+    let (mut app, contract) = time_locked_setup();
+
+    let err = contract
+        .request_withdrawal(
+            &mut app,
+            other_sender(),
+            other_sender().to_string(),
+            vec![],
+            vec![],
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        ContractError::UnauthorizedErr {
+            owner: owner().to_string()
+        },
+        err
+    );
+}
+
+#[test]
+fn claim_stream_should_work() {
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender(), coins(10, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        owner(),
+        COUNTING_LABEL,
+        0,
+        zero_atom(),
+    )
+    .unwrap();
+
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+
+    let start_time = app.block_info().time;
+    contract
+        .create_stream(
+            &mut app,
+            owner(),
+            parent().to_string(),
+            coin(2, ATOM),
+            10,
+            start_time,
+            start_time.plus_seconds(50),
+        )
+        .unwrap();
+
+    let err = contract
+        .claim_stream(&mut app, other_sender(), 0)
+        .unwrap_err();
+    assert_eq!(ContractError::NothingToClaimErr { id: 0 }, err);
+
+    app.update_block(|block| block.time = block.time.plus_seconds(25));
+
+    contract.claim_stream(&mut app, other_sender(), 0).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(parent()).unwrap(),
+        coins(4, ATOM)
+    );
+
+    let stream = contract.query_stream(&app, 0).unwrap();
+    assert_eq!(stream.vested, Uint128::new(4));
+    assert_eq!(stream.claimed, Uint128::new(4));
+
+    let err = contract
+        .claim_stream(&mut app, other_sender(), 0)
+        .unwrap_err();
+    assert_eq!(ContractError::NothingToClaimErr { id: 0 }, err);
+
+    app.update_block(|block| block.time = block.time.plus_seconds(1000));
+
+    contract.claim_stream(&mut app, other_sender(), 0).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(parent()).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![]
+    );
+
+    let streams = contract.query_streams(&app).unwrap().streams;
+    assert_eq!(streams.len(), 1);
+    assert_eq!(streams[0].vested, Uint128::new(10));
+    assert_eq!(streams[0].claimed, Uint128::new(10));
+}
+
+#[test]
+fn claim_stream_should_be_bounded_by_balance() {
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender(), coins(20, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        owner(),
+        COUNTING_LABEL,
+        0,
+        zero_atom(),
+    )
+    .unwrap();
+
+    contract
+        .donate(&mut app, sender(), &coins(5, ATOM))
+        .unwrap();
+
+    let start_time = app.block_info().time;
+    contract
+        .create_stream(
+            &mut app,
+            owner(),
+            parent().to_string(),
+            coin(4, ATOM),
+            10,
+            start_time,
+            start_time.plus_seconds(30),
+        )
+        .unwrap();
+
+    app.update_block(|block| block.time = block.time.plus_seconds(30));
+
+    contract.claim_stream(&mut app, other_sender(), 0).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(parent()).unwrap(),
+        coins(5, ATOM)
+    );
+
+    contract
+        .donate(&mut app, sender(), &coins(15, ATOM))
+        .unwrap();
+
+    contract.claim_stream(&mut app, other_sender(), 0).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(parent()).unwrap(),
+        coins(12, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(8, ATOM)
+    );
+}
+
+#[test]
+fn streams_should_reserve_unclaimed_funds() {
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender(), coins(10, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        owner(),
+        COUNTING_LABEL,
+        0,
+        zero_atom(),
+    )
+    .unwrap();
+
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+
+    let start_time = app.block_info().time;
+    contract
+        .create_stream(
+            &mut app,
+            owner(),
+            parent().to_string(),
+            coin(2, ATOM),
+            10,
+            start_time,
+            start_time.plus_seconds(30),
+        )
+        .unwrap();
+    contract
+        .create_stream(
+            &mut app,
+            owner(),
+            other_sender().to_string(),
+            coin(1, ATOM),
+            10,
+            start_time,
+            start_time.plus_seconds(20),
+        )
+        .unwrap();
+
+    contract.withdraw(&mut app, owner()).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(owner()).unwrap(),
+        coins(2, ATOM)
+    );
+
+    app.update_block(|block| block.time = block.time.plus_seconds(30));
+
+    contract.claim_stream(&mut app, sender(), 0).unwrap();
+    contract.claim_stream(&mut app, sender(), 1).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(parent()).unwrap(),
+        coins(6, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(other_sender()).unwrap(),
+        coins(2, ATOM)
+    );
+}
+
+#[test]
+fn create_stream_should_fail() {
+    let mut app = App::default();
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        owner(),
+        COUNTING_LABEL,
+        0,
+        zero_atom(),
+    )
+    .unwrap();
+
+    let start_time = app.block_info().time;
+
+    let err = contract
+        .create_stream(
+            &mut app,
+            sender(),
+            parent().to_string(),
+            coin(2, ATOM),
+            10,
+            start_time,
+            start_time.plus_seconds(50),
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnauthorizedErr {
+            owner: owner().to_string()
+        },
+        err
+    );
+
+    let err = contract
+        .create_stream(
+            &mut app,
+            owner(),
+            parent().to_string(),
+            coin(2, ATOM),
+            10,
+            start_time,
+            start_time,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidStreamErr {
+            reason: "end time must be after start time".into()
+        },
+        err
+    );
+
+    let err = contract.claim_stream(&mut app, owner(), 0).unwrap_err();
+    assert_eq!(ContractError::StreamNotFoundErr { id: 0 }, err);
+}
+
+fn instantiate_cw20(app: &mut App, initial_balances: Vec<Cw20Coin>) -> Addr {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    let code_id = app.store_code(Box::new(contract));
+
+    app.instantiate_contract(
+        code_id,
+        owner(),
+        &cw20_base::msg::InstantiateMsg {
+            name: "Donation Token".into(),
+            symbol: "DNT".into(),
+            decimals: 6,
+            initial_balances,
+            mint: None,
+            marketing: None,
+        },
+        &[],
+        "cw20",
+        None,
+    )
+    .unwrap()
+}
+
+#[test]
+fn donate_cw20_should_work() {
+    let mut app = App::default();
+
+    let token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin {
+            address: sender().to_string(),
+            amount: Uint128::new(100),
+        }],
+    );
+    let other_token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin {
+            address: sender().to_string(),
+            amount: Uint128::new(100),
+        }],
+    );
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        owner(),
+        COUNTING_LABEL,
+        0,
+        ten_atom(),
+    )
+    .unwrap();
+
+    contract.allow_cw20(&mut app, owner(), &token, 10).unwrap();
+
+    contract.donate_cw20(&mut app, sender(), &token, 5).unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 0);
+
+    let resp = contract
+        .donate_cw20(&mut app, sender(), &token, 10)
+        .unwrap();
+    assert_events(&resp.events).contains(
+        EventMatcher::wasm()
+            .contract(&contract.addr())
+            .action("donate")
+            .attr("sender", sender()),
+    );
+    assert_eq!(contract.query_value(&app).unwrap().value, 1);
+
+    assert_eq!(
+        CountingContract::query_cw20_balance(&app, &token, contract.addr()).unwrap(),
+        Uint128::new(15)
+    );
+
+    let err = contract
+        .donate_cw20(&mut app, sender(), &other_token, 10)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnsupportedCw20Err {
+            token: other_token.to_string()
+        },
+        err
+    );
+
+    let err = contract
+        .allow_cw20(&mut app, sender(), &other_token, 0)
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnauthorizedErr {
+            owner: owner().to_string()
+        },
+        err
+    );
+
+    let tokens = contract.query_cw20_tokens(&app).unwrap().tokens;
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].token, token);
+    assert_eq!(tokens[0].minimal_donation, Uint128::new(10));
+}
+
+#[test]
+fn withdraw_cw20_should_work() {
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender(), coins(10, ATOM))
+            .unwrap();
+    });
+
+    let token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin {
+            address: sender().to_string(),
+            amount: Uint128::new(100),
+        }],
+    );
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        owner(),
+        COUNTING_LABEL,
+        0,
+        zero_atom(),
+    )
+    .unwrap();
+
+    contract.allow_cw20(&mut app, owner(), &token, 0).unwrap();
+    contract
+        .donate_cw20(&mut app, sender(), &token, 50)
+        .unwrap();
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+
+    contract
+        .withdraw_to_with_cw20(
+            &mut app,
+            owner(),
+            parent().to_string(),
+            vec![],
+            vec![Cw20Coin {
+                address: token.to_string(),
+                amount: Uint128::new(20),
+            }],
+        )
+        .unwrap();
+
+    assert_eq!(
+        CountingContract::query_cw20_balance(&app, &token, parent()).unwrap(),
+        Uint128::new(20)
+    );
+    assert_eq!(app.wrap().query_all_balances(parent()).unwrap(), vec![]);
+
+    contract.withdraw(&mut app, owner()).unwrap();
+
+    assert_eq!(
+        CountingContract::query_cw20_balance(&app, &token, owner()).unwrap(),
+        Uint128::new(30)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(owner()).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(
+        CountingContract::query_cw20_balance(&app, &token, contract.addr()).unwrap(),
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn time_locked_cw20_withdrawal_should_work() {
+    let (mut app, contract) = time_locked_setup();
+
+    let token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin {
+            address: sender().to_string(),
+            amount: Uint128::new(100),
+        }],
+    );
+
+    contract.allow_cw20(&mut app, owner(), &token, 0).unwrap();
+    contract
+        .donate_cw20(&mut app, sender(), &token, 50)
+        .unwrap();
+
+    let cw20_funds = vec![Cw20Coin {
+        address: token.to_string(),
+        amount: Uint128::new(20),
+    }];
+    contract
+        .request_withdrawal(
+            &mut app,
+            owner(),
+            parent().to_string(),
+            vec![],
+            cw20_funds.clone(),
+        )
+        .unwrap();
+    assert_eq!(
+        contract
+            .query_pending_withdrawal(&app, 0)
+            .unwrap()
+            .cw20_funds,
+        cw20_funds
+    );
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    contract.execute_withdrawal(&mut app, sender(), 0).unwrap();
+
+    assert_eq!(
+        CountingContract::query_cw20_balance(&app, &token, parent()).unwrap(),
+        Uint128::new(20)
+    );
+    assert_eq!(app.wrap().query_all_balances(parent()).unwrap(), vec![]);
+
+    // An empty request drains both native and cw20 funds, like Withdraw.
+    contract
+        .request_withdrawal(&mut app, owner(), owner().to_string(), vec![], vec![])
+        .unwrap();
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    contract.execute_withdrawal(&mut app, sender(), 1).unwrap();
+
+    assert_eq!(
+        CountingContract::query_cw20_balance(&app, &token, owner()).unwrap(),
+        Uint128::new(30)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(owner()).unwrap(),
+        coins(10, ATOM)
+    );
+}
+
+#[test]
+fn donate_cw20_parent_should_work() {
+    let CountingSuite {
+        mut app,
+        contract,
+        mut parents,
+        ..
+    } = CountingSuiteBuilder::new()
+        .with_minimal_donation(ten_atom())
+        .with_parent(ten_atom(), 1, Decimal::percent(10))
+        .build()
+        .unwrap();
+    let parent_contract = parents.remove(0);
+
+    let token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin {
+            address: sender().to_string(),
+            amount: Uint128::new(100),
+        }],
+    );
+
+    parent_contract
+        .allow_cw20(&mut app, owner(), &token, 5)
+        .unwrap();
+    contract.allow_cw20(&mut app, owner(), &token, 5).unwrap();
+
+    contract
+        .donate_cw20(&mut app, sender(), &token, 100)
+        .unwrap();
+
+    assert_eq!(contract.query_value(&app).unwrap().value, 1);
+    assert_eq!(parent_contract.query_value(&app).unwrap().value, 1);
+
+    assert_eq!(
+        CountingContract::query_cw20_balance(&app, &token, contract.addr()).unwrap(),
+        Uint128::new(90)
+    );
+    assert_eq!(
+        CountingContract::query_cw20_balance(&app, &token, parent_contract.addr()).unwrap(),
+        Uint128::new(10)
+    );
+}
+
+#[test]
+fn donate_cw20_parent_not_allowed_should_keep_tokens() {
+    let CountingSuite {
+        mut app,
+        contract,
+        mut parents,
+        ..
+    } = CountingSuiteBuilder::new()
+        .with_minimal_donation(ten_atom())
+        .with_parent(ten_atom(), 1, Decimal::percent(10))
+        .build()
+        .unwrap();
+    let parent_contract = parents.remove(0);
+
+    let token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin {
+            address: sender().to_string(),
+            amount: Uint128::new(100),
+        }],
+    );
+
+    contract.allow_cw20(&mut app, owner(), &token, 5).unwrap();
+
+    contract
+        .donate_cw20(&mut app, sender(), &token, 100)
+        .unwrap();
+
+    assert_eq!(contract.query_value(&app).unwrap().value, 1);
+    assert_eq!(parent_contract.query_value(&app).unwrap().value, 0);
+
+    assert_eq!(
+        CountingContract::query_cw20_balance(&app, &token, contract.addr()).unwrap(),
+        Uint128::new(100)
+    );
+    assert_eq!(
+        CountingContract::query_cw20_balance(&app, &token, parent_contract.addr()).unwrap(),
+        Uint128::zero()
+    );
+}
+
+#[cfg(not(feature = "cosmwasm_2"))]
+mod receipt_nft {
+    use cosmwasm_std::{Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult};
+    pub use cw721::{NftInfoResponse, OwnerOfResponse};
+
+    use crate::msg::ReceiptMetadata;
+
+    pub type InstantiateMsg = cw721_base::InstantiateMsg;
+    pub type QueryMsg = cw721_base::QueryMsg<Empty>;
+    type ReceiptNft<'a> = cw721_base::Cw721Contract<'a, ReceiptMetadata, Empty, Empty, Empty>;
+
+    pub fn execute(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: cw721_base::ExecuteMsg<ReceiptMetadata, Empty>,
+    ) -> Result<Response, cw721_base::ContractError> {
+        ReceiptNft::default().execute(deps, env, info, msg)
+    }
+
+    pub fn instantiate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> StdResult<Response> {
+        ReceiptNft::default().instantiate(deps, env, info, msg)
+    }
+
+    pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+        ReceiptNft::default().query(deps, env, msg)
+    }
+
+    pub fn instantiate_msg(minter: String) -> InstantiateMsg {
+        InstantiateMsg {
+            name: "Donation Receipts".into(),
+            symbol: "RCPT".into(),
+            minter,
+        }
+    }
+}
+
+#[cfg(feature = "cosmwasm_2")]
+mod receipt_nft {
+    use cosmwasm_std::{Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response};
+    pub use cw721::msg::{NftInfoResponse, OwnerOfResponse};
+    use cw721::{
+        error::Cw721ContractError,
+        extension::Cw721Extensions,
+        msg::{Cw721ExecuteMsg, Cw721InstantiateMsg, Cw721QueryMsg},
+        traits::{Contains, Cw721CustomMsg, Cw721Execute, Cw721Query, Cw721State, StateFactory},
+    };
+
+    use crate::msg::ReceiptMetadata;
+
+    pub type InstantiateMsg = Cw721InstantiateMsg<Empty>;
+    pub type QueryMsg = Cw721QueryMsg<ReceiptMetadata, Empty, Empty>;
+    type ReceiptNft<'a> =
+        Cw721Extensions<'a, ReceiptMetadata, ReceiptMetadata, Empty, Empty, Empty, Empty, Empty>;
+
+    // cw721 0.20 only stores extensions that can be built from the mint message.
+    impl Cw721State for ReceiptMetadata {}
+    impl Cw721CustomMsg for ReceiptMetadata {}
+
+    impl Contains for ReceiptMetadata {
+        fn contains(&self, other: &Self) -> bool {
+            self == other
+        }
+    }
+
+    impl StateFactory<ReceiptMetadata> for ReceiptMetadata {
+        fn create(
+            &self,
+            _deps: Deps,
+            _env: &Env,
+            _info: Option<&MessageInfo>,
+            _current: Option<&ReceiptMetadata>,
+        ) -> Result<ReceiptMetadata, Cw721ContractError> {
+            Ok(self.clone())
+        }
+
+        fn validate(
+            &self,
+            _deps: Deps,
+            _env: &Env,
+            _info: Option<&MessageInfo>,
+            _current: Option<&ReceiptMetadata>,
+        ) -> Result<(), Cw721ContractError> {
+            Ok(())
+        }
+    }
+
+    pub fn execute(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: Cw721ExecuteMsg<ReceiptMetadata, Empty, Empty>,
+    ) -> Result<Response, Cw721ContractError> {
+        ReceiptNft::default().execute(deps, &env, &info, msg)
+    }
+
+    pub fn instantiate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> Result<Response, Cw721ContractError> {
+        ReceiptNft::default().instantiate(deps, &env, &info, msg)
+    }
+
+    pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, Cw721ContractError> {
+        ReceiptNft::default().query(deps, &env, msg)
+    }
+
+    pub fn instantiate_msg(minter: String) -> InstantiateMsg {
+        InstantiateMsg {
+            name: "Donation Receipts".into(),
+            symbol: "RCPT".into(),
+            collection_info_extension: Empty {},
+            minter: Some(minter),
+            creator: None,
+            withdraw_address: None,
+        }
+    }
+}
+
+fn instantiate_receipt_nft(app: &mut App, minter: Addr) -> Addr {
+    let contract = ContractWrapper::new(
+        receipt_nft::execute,
+        receipt_nft::instantiate,
+        receipt_nft::query,
+    );
+    let code_id = app.store_code(Box::new(contract));
+
+    app.instantiate_contract(
+        code_id,
+        owner(),
+        &receipt_nft::instantiate_msg(minter.into_string()),
+        &[],
+        "cw721",
+        None,
+    )
+    .unwrap()
+}
+
+#[test]
+fn donate_receipt_should_work() {
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender(), coins(20, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        owner(),
+        COUNTING_LABEL,
+        0,
+        ten_atom(),
+    )
+    .unwrap();
+
+    let nft = instantiate_receipt_nft(&mut app, contract.addr());
+
+    let err = contract
+        .set_receipt_contract(&mut app, sender(), nft.to_string())
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnauthorizedErr {
+            owner: owner().to_string()
+        },
+        err
+    );
+
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+
+    contract
+        .set_receipt_contract(&mut app, owner(), nft.to_string())
+        .unwrap();
+
+    contract.donate(&mut app, sender(), EMPTY_FUNDS).unwrap();
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+
+    let owner_of: OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &nft,
+            &receipt_nft::QueryMsg::OwnerOf {
+                token_id: "1".into(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(owner_of.owner, sender().to_string());
+
+    let info: NftInfoResponse<ReceiptMetadata> = app
+        .wrap()
+        .query_wasm_smart(
+            &nft,
+            &receipt_nft::QueryMsg::NftInfo {
+                token_id: "1".into(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        info.extension,
+        ReceiptMetadata {
+            donor: sender(),
+            amount: coins(10, ATOM),
+            counter: 2,
+        }
+    );
+
+    app.wrap()
+        .query_wasm_smart::<NftInfoResponse<ReceiptMetadata>>(
+            &nft,
+            &receipt_nft::QueryMsg::NftInfo {
+                token_id: "2".into(),
+            },
+        )
+        .unwrap_err();
+
+    contract
+        .set_receipt_contract(&mut app, owner(), None)
+        .unwrap();
+    assert_eq!(
+        contract.query_receipt_contract(&app).unwrap().contract,
+        None
+    );
+}
+
+#[test]
+fn matching_pool_should_work() {
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender(), coins(20, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &other_sender(), coins(10, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &owner(), coins(20, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        owner(),
+        COUNTING_LABEL,
+        0,
+        zero_atom(),
+    )
+    .unwrap();
+
+    let ends_at = app.block_info().time.plus_seconds(100);
+    contract
+        .configure_matching(
+            &mut app,
+            owner(),
+            ATOM,
+            Decimal::percent(50),
+            8,
+            12,
+            ends_at,
+        )
+        .unwrap();
+
+    let err = contract
+        .fund_matching_pool(&mut app, sender(), &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnauthorizedErr {
+            owner: owner().to_string()
+        },
+        err
+    );
+
+    contract
+        .fund_matching_pool(&mut app, owner(), &coins(20, ATOM))
+        .unwrap();
+
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, other_sender(), &coins(10, ATOM))
+        .unwrap();
+
+    let matching = contract.query_matching(&app).unwrap();
+    assert_eq!(matching.pool_remaining, Uint128::new(8));
+    assert_eq!(matching.matched_total, Uint128::new(12));
+    assert_eq!(
+        contract
+            .query_matched_donation(&app, sender())
+            .unwrap()
+            .matched,
+        Uint128::new(8)
+    );
+    assert_eq!(
+        contract
+            .query_matched_donation(&app, other_sender())
+            .unwrap()
+            .matched,
+        Uint128::new(4)
+    );
+
+    contract.withdraw(&mut app, owner()).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(owner()).unwrap(),
+        coins(30, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(20, ATOM)
+    );
+
+    let err = contract
+        .configure_matching(
+            &mut app,
+            owner(),
+            ATOM,
+            Decimal::one(),
+            10,
+            10,
+            ends_at.plus_seconds(100),
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::MatchingActiveErr {}, err);
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    let err = contract
+        .fund_matching_pool(&mut app, owner(), &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(ContractError::MatchingEndedErr {}, err);
+
+    contract.withdraw(&mut app, owner()).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(owner()).unwrap(),
+        coins(50, ATOM)
+    );
+}
+
+#[test]
+fn fund_matching_pool_should_fail() {
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &owner(), vec![coin(10, ATOM), coin(10, OSMO)])
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        owner(),
+        COUNTING_LABEL,
+        0,
+        zero_atom(),
+    )
+    .unwrap();
+
+    let err = contract
+        .fund_matching_pool(&mut app, owner(), &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(ContractError::NoMatchingErr {}, err);
+
+    let ends_at = app.block_info().time.plus_seconds(100);
+    contract
+        .configure_matching(&mut app, owner(), ATOM, Decimal::one(), 10, 10, ends_at)
+        .unwrap();
+
+    let err = contract
+        .fund_matching_pool(&mut app, owner(), &coins(10, OSMO))
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidMatchingFundsErr { denom: ATOM.into() },
+        err
+    );
+}
+
+fn campaign_setup(target: u128) -> (App, CountingContract) {
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender(), coins(50, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &other_sender(), coins(10, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        owner(),
+        COUNTING_LABEL,
+        0,
+        zero_atom(),
+    )
+    .unwrap();
+
+    let start_time = app.block_info().time;
+    contract
+        .create_campaign(
+            &mut app,
+            owner(),
+            coins(target, ATOM),
+            start_time,
+            start_time.plus_seconds(100),
+            true,
+        )
+        .unwrap();
+
+    (app, contract)
+}
+
+#[test]
+fn campaign_refund_should_work() {
+    let (mut app, contract) = campaign_setup(100);
+
+    contract
+        .donate_to_campaign(&mut app, sender(), 0, &coins(30, ATOM))
+        .unwrap();
+    contract
+        .donate_to_campaign(&mut app, other_sender(), 0, &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+
+    assert_eq!(contract.query_value(&app).unwrap().value, 3);
+
+    let err = contract.claim_refund(&mut app, sender(), 0).unwrap_err();
+    assert_eq!(ContractError::CampaignNotFailedErr { id: 0 }, err);
+
+    contract.withdraw(&mut app, owner()).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(owner()).unwrap(),
+        coins(10, ATOM)
+    );
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    let campaign = contract.query_campaign(&app, 0).unwrap();
+    assert_eq!(campaign.status, CampaignStatus::Failed);
+    assert_eq!(campaign.raised, coins(40, ATOM));
+
+    contract.withdraw(&mut app, owner()).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(owner()).unwrap(),
+        coins(10, ATOM)
+    );
+
+    contract.claim_refund(&mut app, sender(), 0).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(sender()).unwrap(),
+        coins(40, ATOM)
+    );
+
+    let err = contract.claim_refund(&mut app, sender(), 0).unwrap_err();
+    assert_eq!(ContractError::NothingToRefundErr { id: 0 }, err);
+
+    contract.claim_refund(&mut app, other_sender(), 0).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![]
+    );
+
+    let campaign = contract.query_campaign(&app, 0).unwrap();
+    assert_eq!(campaign.refunded, coins(40, ATOM));
+}
+
+#[test]
+fn campaign_success_should_release_funds() {
+    let (mut app, contract) = campaign_setup(40);
+
+    contract
+        .donate_to_campaign(&mut app, sender(), 0, &coins(50, ATOM))
+        .unwrap();
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    let campaign = contract.query_campaign(&app, 0).unwrap();
+    assert_eq!(campaign.status, CampaignStatus::Succeeded);
+
+    let err = contract.claim_refund(&mut app, sender(), 0).unwrap_err();
+    assert_eq!(ContractError::CampaignNotFailedErr { id: 0 }, err);
+
+    contract.withdraw(&mut app, owner()).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(owner()).unwrap(),
+        coins(50, ATOM)
+    );
+}
+
+#[test]
+fn donate_to_campaign_should_fail() {
+    let (mut app, contract) = campaign_setup(100);
+
+    let err = contract
+        .donate_to_campaign(&mut app, sender(), 1, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(ContractError::CampaignNotFoundErr { id: 1 }, err);
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    let err = contract
+        .donate_to_campaign(&mut app, sender(), 0, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(ContractError::CampaignNotActiveErr { id: 0 }, err);
+
+    let start_time = app.block_info().time;
+    let err = contract
+        .create_campaign(
+            &mut app,
+            sender(),
+            coins(10, ATOM),
+            start_time,
+            start_time.plus_seconds(100),
+            false,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::UnauthorizedErr {
+            owner: owner().to_string()
+        },
+        err
+    );
+}
+
+fn refund_setup() -> (App, CountingContract) {
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender(), coins(50, ATOM))
+            .unwrap();
+    });
+
+    let contract_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        owner(),
+        COUNTING_LABEL,
+        0,
+        ten_atom(),
+    )
+    .unwrap();
+
+    contract
+        .set_refund_grace_period(&mut app, owner(), 100)
+        .unwrap();
+
+    (app, contract)
+}
+
+#[test]
+fn refund_donation_should_work() {
+    let (mut app, contract) = refund_setup();
+
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, sender(), &coins(20, ATOM))
+        .unwrap();
+
+    assert_eq!(contract.query_value(&app).unwrap().value, 2);
+
+    let donation = contract.query_donation(&app, 1).unwrap();
+    assert_eq!(donation.donor, sender());
+    assert_eq!(donation.funds, coins(20, ATOM));
+    assert_eq!(
+        donation.refundable_until,
+        app.block_info().time.plus_seconds(100)
+    );
+
+    let err = contract.refund_donation(&mut app, owner(), 1).unwrap_err();
+    assert_eq!(
+        ContractError::UnauthorizedErr {
+            owner: sender().to_string()
+        },
+        err
+    );
+
+    contract.refund_donation(&mut app, sender(), 1).unwrap();
+
+    assert_eq!(contract.query_value(&app).unwrap().value, 1);
+    assert_eq!(
+        app.wrap().query_all_balances(sender()).unwrap(),
+        coins(40, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(10, ATOM)
+    );
+
+    let err = contract.refund_donation(&mut app, sender(), 1).unwrap_err();
+    assert_eq!(ContractError::DonationNotFoundErr { id: 1 }, err);
+}
+
+#[test]
+fn refund_donation_after_grace_period_should_fail() {
+    let (mut app, contract) = refund_setup();
+
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+
+    contract.withdraw(&mut app, owner()).unwrap();
+    assert_eq!(app.wrap().query_all_balances(owner()).unwrap(), vec![]);
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    let err = contract.refund_donation(&mut app, sender(), 0).unwrap_err();
+    assert_eq!(ContractError::RefundWindowClosedErr { id: 0 }, err);
+
+    contract.withdraw(&mut app, owner()).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(owner()).unwrap(),
+        coins(10, ATOM)
+    );
+}
+
+#[test]
+fn refund_forwarded_donation_should_fail() {
+    let CountingSuite {
+        mut app,
+        contract,
+        mut parents,
+        ..
+    } = CountingSuiteBuilder::new()
+        .with_balance(&sender(), &coins(50, ATOM))
+        .with_minimal_donation(ten_atom())
+        .with_parent(zero_atom(), 2, Decimal::percent(10))
+        .build()
+        .unwrap();
+    let parent_contract = parents.remove(0);
+
+    contract
+        .set_refund_grace_period(&mut app, owner(), 100)
+        .unwrap();
+
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+    contract.refund_donation(&mut app, sender(), 0).unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 0);
+
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+
+    assert_eq!(
+        app.wrap()
+            .query_all_balances(parent_contract.addr())
+            .unwrap(),
+        coins(2, ATOM)
+    );
+
+    let err = contract.refund_donation(&mut app, sender(), 1).unwrap_err();
+    assert_eq!(ContractError::DonationForwardedErr { id: 1 }, err);
+
+    let err = contract.refund_donation(&mut app, sender(), 2).unwrap_err();
+    assert_eq!(ContractError::DonationForwardedErr { id: 2 }, err);
+}
+
+#[test]
+fn refund_claimed_campaign_donation_should_fail() {
+    let (mut app, contract) = campaign_setup(100);
+
+    contract
+        .set_refund_grace_period(&mut app, owner(), 200)
+        .unwrap();
+    contract
+        .donate_to_campaign(&mut app, sender(), 0, &coins(30, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, other_sender(), &coins(10, ATOM))
+        .unwrap();
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    contract.claim_refund(&mut app, sender(), 0).unwrap();
+
+    let err = contract.refund_donation(&mut app, sender(), 0).unwrap_err();
+    assert_eq!(ContractError::CampaignEndedErr { id: 0 }, err);
+    assert_eq!(
+        app.wrap().query_all_balances(sender()).unwrap(),
+        coins(50, ATOM)
+    );
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+
+    contract.withdraw(&mut app, owner()).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(owner()).unwrap(),
+        coins(10, ATOM)
+    );
+}
+
+#[test]
+fn refund_donation_should_return_match() {
+    let CountingSuite {
+        mut app, contract, ..
+    } = CountingSuiteBuilder::new()
+        .with_balance(&sender(), &coins(50, ATOM))
+        .with_balance(&owner(), &coins(20, ATOM))
+        .with_minimal_donation(ten_atom())
+        .build()
+        .unwrap();
+
+    contract
+        .set_refund_grace_period(&mut app, owner(), 100)
+        .unwrap();
+
+    let ends_at = app.block_info().time.plus_seconds(200);
+    contract
+        .configure_matching(
+            &mut app,
+            owner(),
+            ATOM,
+            Decimal::percent(50),
+            8,
+            12,
+            ends_at,
+        )
+        .unwrap();
+    contract
+        .fund_matching_pool(&mut app, owner(), &coins(20, ATOM))
+        .unwrap();
+
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+
+    contract.refund_donation(&mut app, sender(), 1).unwrap();
+
+    let matching = contract.query_matching(&app).unwrap();
+    assert_eq!(matching.pool_remaining, Uint128::new(15));
+    assert_eq!(matching.matched_total, Uint128::new(5));
+    assert_eq!(
+        contract
+            .query_matched_donation(&app, sender())
+            .unwrap()
+            .matched,
+        Uint128::new(5)
+    );
+
+    contract
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+
+    let matching = contract.query_matching(&app).unwrap();
+    assert_eq!(matching.pool_remaining, Uint128::new(12));
+    assert_eq!(matching.matched_total, Uint128::new(8));
+}
+
+const IBC_CHANNEL: &str = "channel-0";
+const IBC_VERSION: &str = "counting-1";
+
+type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+fn message_info(sender: &Addr, funds: &[Coin]) -> MessageInfo {
+    MessageInfo {
+        sender: sender.clone(),
+        funds: funds.to_vec(),
+    }
+}
+
+fn set_contract_balance(deps: &mut MockDeps, balance: Vec<Coin>) {
+    #[cfg(not(feature = "cosmwasm_2"))]
+    deps.querier
+        .update_balance(mock_env().contract.address, balance);
+    #[cfg(feature = "cosmwasm_2")]
+    deps.querier
+        .bank
+        .update_balance(mock_env().contract.address, balance);
+}
+
+fn ibc_counting_contract() -> MockDeps {
+    ibc_counting_contract_with_msg(instantiate_msg())
+}
+
+fn ibc_counting_contract_with_msg(msg: InstantiateMsg) -> MockDeps {
+    let mut deps = mock_dependencies();
+
+    instantiate(deps.as_mut(), mock_env(), message_info(&owner(), &[]), msg).unwrap();
+
+    ibc_channel_open(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_open_init(IBC_CHANNEL, IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap();
+    ibc_channel_connect(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_connect_ack(IBC_CHANNEL, IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap();
+
+    deps
+}
+
+fn ibc_query_value(deps: &MockDeps) -> u64 {
+    let resp = query(deps.as_ref(), mock_env(), QueryMsg::Value {}).unwrap();
+    from_json::<ValueResp>(&resp).unwrap().value
+}
+
+fn ibc_donate_remote(deps: &mut MockDeps, funds: &[Coin]) -> IbcPacketMsg {
+    let resp = execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender(), funds),
+        ExecMsg::DonateRemote {
+            channel: IBC_CHANNEL.to_owned(),
+        },
+    )
+    .unwrap();
+
+    match &resp.messages[0].msg {
+        CosmosMsg::Ibc(IbcMsg::SendPacket {
+            channel_id, data, ..
+        }) => {
+            assert_eq!(channel_id, IBC_CHANNEL);
+            from_json(data).unwrap()
+        }
+        msg => panic!("Unexpected message: {msg:?}"),
+    }
+}
+
+fn ibc_relay(parent: &mut MockDeps, packet: &impl Serialize) -> IbcAcknowledgement {
+    let resp = ibc_packet_receive(
+        parent.as_mut(),
+        mock_env(),
+        mock_ibc_packet_recv(IBC_CHANNEL, packet).unwrap(),
+    )
+    .unwrap();
+
+    // 2.x allows receiving without an acknowledgement, the contract always writes one.
+    #[cfg(feature = "cosmwasm_2")]
+    let ack = resp.acknowledgement.unwrap();
+    #[cfg(not(feature = "cosmwasm_2"))]
+    let ack = resp.acknowledgement;
+
+    IbcAcknowledgement::new(ack)
+}
+
+#[test]
+fn ibc_channel_handshake_should_work() {
+    let mut deps = mock_dependencies();
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner(), &[]),
+        instantiate_msg(),
+    )
+    .unwrap();
+
+    let err = ibc_channel_open(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_open_init(IBC_CHANNEL, IbcOrder::Ordered, IBC_VERSION),
+    )
+    .unwrap_err();
+    assert_eq!(ContractError::InvalidIbcOrderErr {}, err);
+
+    let err = ibc_channel_open(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_open_init(IBC_CHANNEL, IbcOrder::Unordered, "ics20-1"),
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidIbcVersionErr {
+            version: "ics20-1".to_owned()
+        },
+        err
+    );
+
+    ibc_channel_connect(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_connect_ack(IBC_CHANNEL, IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap();
+
+    let resp = query(deps.as_ref(), mock_env(), QueryMsg::IbcChannels {}).unwrap();
+    assert_eq!(
+        from_json::<IbcChannelsResp>(&resp).unwrap().channels,
+        vec![IBC_CHANNEL.to_owned()]
+    );
+
+    ibc_channel_close(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_close_init(IBC_CHANNEL, IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender(), &coins(10, ATOM)),
+        ExecMsg::DonateRemote {
+            channel: IBC_CHANNEL.to_owned(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::UnknownChannelErr {
+            channel: IBC_CHANNEL.to_owned()
+        },
+        err
+    );
+}
+
+#[test]
+fn ibc_donate_should_increment_remote_parent() {
+    let mut contract = ibc_counting_contract();
+    let mut parent_contract = ibc_counting_contract_with_msg(zero_funds_instantiate_msg());
+
+    let packet = ibc_donate_remote(&mut contract, &coins(10, ATOM));
+    assert_eq!(
+        packet,
+        IbcPacketMsg::Donate {
+            id: 0,
+            donor: sender().to_string(),
+            funds: coins(10, ATOM),
+        }
+    );
+
+    let ack = ibc_relay(&mut parent_contract, &packet);
+    assert_eq!(
+        from_json::<IbcAck>(&ack.data).unwrap(),
+        IbcAck::Result(to_json_binary(&1u64).unwrap())
+    );
+    assert_eq!(ibc_query_value(&parent_contract), 1);
+    assert_eq!(ibc_query_value(&contract), 0);
+
+    let resp = ibc_packet_ack(
+        contract.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(IBC_CHANNEL, &packet, ack).unwrap(),
+    )
+    .unwrap();
+    assert!(resp.messages.is_empty());
+
+    query(
+        contract.as_ref(),
+        mock_env(),
+        QueryMsg::IbcDonation { id: 0 },
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn ibc_donate_with_minimal_should_refund() {
+    let mut contract = ibc_counting_contract();
+    let mut parent_contract = ibc_counting_contract();
+
+    let packet = ibc_donate_remote(&mut contract, &coins(10, ATOM));
+    let ack = ibc_relay(&mut parent_contract, &packet);
+    assert_eq!(
+        from_json::<IbcAck>(&ack.data).unwrap(),
+        IbcAck::Error(
+            ContractError::UnverifiedRemoteDonationErr {
+                minimal: coin(10, ATOM)
+            }
+            .to_string()
+        )
+    );
+    assert_eq!(ibc_query_value(&parent_contract), 0);
+
+    let resp = ibc_packet_ack(
+        contract.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(IBC_CHANNEL, &packet, ack).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        resp.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: sender().to_string(),
+            amount: coins(10, ATOM),
+        })
+    );
+}
+
+#[test]
+fn ibc_donate_error_ack_should_refund() {
+    let mut contract = ibc_counting_contract();
+    let mut parent_contract = ibc_counting_contract();
+
+    let ack = ibc_relay(&mut parent_contract, &"invalid");
+    assert!(matches!(
+        from_json::<IbcAck>(&ack.data).unwrap(),
+        IbcAck::Error(_)
+    ));
+
+    let packet = ibc_donate_remote(&mut contract, &coins(10, ATOM));
+    let resp = ibc_packet_ack(
+        contract.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(IBC_CHANNEL, &packet, ack).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        resp.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: sender().to_string(),
+            amount: coins(10, ATOM),
+        })
+    );
+}
+
+#[test]
+fn ibc_donate_timeout_should_refund() {
+    let mut contract = ibc_counting_contract();
+
+    let packet = ibc_donate_remote(&mut contract, &coins(10, ATOM));
+    set_contract_balance(&mut contract, coins(10, ATOM));
+
+    let resp = execute(
+        contract.as_mut(),
+        mock_env(),
+        message_info(&owner(), &[]),
+        ExecMsg::Withdraw {},
+    )
+    .unwrap();
+    assert!(resp.messages.is_empty());
+
+    let resp = ibc_packet_timeout(
+        contract.as_mut(),
+        mock_env(),
+        mock_ibc_packet_timeout(IBC_CHANNEL, &packet).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        resp.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: sender().to_string(),
+            amount: coins(10, ATOM),
+        })
+    );
+
+    let err = ibc_packet_timeout(
+        contract.as_mut(),
+        mock_env(),
+        mock_ibc_packet_timeout(IBC_CHANNEL, &packet).unwrap(),
+    )
+    .unwrap_err();
+    assert_eq!(ContractError::IbcDonationNotFoundErr { id: 0 }, err);
+}
+
+const TRANSFER_CHANNEL: &str = "channel-7";
+
+fn inj_prefix(lengths: Vec<u32>) -> AllowedPrefix {
+    AllowedPrefix {
+        prefix: "inj".to_owned(),
+        lengths,
+        channel: TRANSFER_CHANNEL.to_owned(),
+    }
+}
+
+fn remote_parent_setup() -> MockDeps {
+    let mut deps = mock_dependencies();
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner(), &[]),
+        InstantiateMsg {
+            parent: Some(Parent {
+                addr: remote_parent().to_string(),
+                donating_period: 1,
+                part: Decimal::percent(10),
+                remote: Some(RemoteParent {
+                    channel: TRANSFER_CHANNEL.to_owned(),
+                    timeout: 600,
+                }),
+            }),
+            allowed_prefixes: vec![inj_prefix(vec![20])],
+            ..zero_funds_instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    set_contract_balance(&mut deps, coins(100, ATOM));
+
+    deps
+}
+
+fn remote_parent_donate(deps: &mut MockDeps) -> (Ics20Packet, SubMsg) {
+    let resp = execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender(), &coins(100, ATOM)),
+        ExecMsg::Donate { campaign: None },
+    )
+    .unwrap();
+
+    assert_eq!(
+        resp.messages[0].msg,
+        CosmosMsg::Ibc(IbcMsg::Transfer {
+            channel_id: TRANSFER_CHANNEL.to_owned(),
+            to_address: remote_parent().to_string(),
+            amount: coin(10, ATOM),
+            timeout: IbcTimeout::with_timestamp(mock_env().block.time.plus_seconds(600)),
+            #[cfg(feature = "cosmwasm_2")]
+            memo: Some(
+                to_json_string(&IbcCallbackRequest::source(IbcSrcCallback {
+                    address: mock_env().contract.address,
+                    gas_limit: None,
+                }))
+                .unwrap()
+            ),
+        })
+    );
+
+    let packet = Ics20Packet {
+        denom: ATOM.to_owned(),
+        amount: Uint128::new(10),
+        sender: mock_env().contract.address.to_string(),
+        receiver: remote_parent().to_string(),
+        memo: None,
+    };
+
+    (packet, resp.messages[0].clone())
+}
+
+// Answers a transfer submessage like the transfer module does, with the packet sequence.
+#[cfg(feature = "cosmwasm_2")]
+fn remote_parent_transfer_reply(deps: &mut MockDeps, msg: &SubMsg, mut sequence: u64) {
+    assert_eq!(msg.reply_on, ReplyOn::Success);
+
+    let mut data = vec![0x08];
+    while sequence >= 0x80 {
+        data.push(sequence as u8 | 0x80);
+        sequence >>= 7;
+    }
+    data.push(sequence as u8);
+
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: msg.id,
+            payload: msg.payload.clone(),
+            gas_used: 0,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+                msg_responses: vec![MsgResponse {
+                    type_url: "/ibc.applications.transfer.v1.MsgTransferResponse".to_owned(),
+                    value: Binary::from(data),
+                }],
+            }),
+        },
+    )
+    .unwrap();
+}
+
+#[cfg(feature = "cosmwasm_2")]
+fn query_parent_transfers(deps: &MockDeps) -> ParentTransfersResp {
+    let resp = query(deps.as_ref(), mock_env(), QueryMsg::ParentTransfers {}).unwrap();
+    from_json(&resp).unwrap()
+}
+
+#[cfg(not(feature = "cosmwasm_2"))]
+#[test]
+fn remote_parent_transfer_should_not_be_tracked() {
+    let mut deps = remote_parent_setup();
+
+    let (_, msg) = remote_parent_donate(&mut deps);
+
+    assert_eq!(msg.reply_on, ReplyOn::Never);
+}
+
+#[cfg(feature = "cosmwasm_2")]
+#[test]
+fn remote_parent_transfer_ack_should_work() {
+    let mut deps = remote_parent_setup();
+
+    let (packet, msg) = remote_parent_donate(&mut deps);
+    assert_eq!(query_parent_transfers(&deps).transfers, vec![]);
+    remote_parent_transfer_reply(&mut deps, &msg, 5);
+
+    let transfers = query_parent_transfers(&deps).transfers;
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].channel, TRANSFER_CHANNEL);
+    assert_eq!(transfers[0].sequence, 5);
+    assert_eq!(transfers[0].receiver, remote_parent().to_string());
+    assert_eq!(transfers[0].amount, coin(10, ATOM));
+
+    let ack = to_json_binary(&IbcAck::Result(Binary::from(vec![1]))).unwrap();
+    let mut msg =
+        mock_ibc_packet_ack(TRANSFER_CHANNEL, &packet, IbcAcknowledgement::new(ack)).unwrap();
+    msg.original_packet.sequence = 5;
+
+    // ICS-20 acknowledgements go to the transfer module, never to this contract.
+    ibc_packet_ack(deps.as_mut(), mock_env(), msg.clone()).unwrap_err();
+
+    let resp = ibc_source_callback(
+        deps.as_mut(),
+        mock_env(),
+        IbcSourceCallbackMsg::Acknowledgement(IbcAckCallbackMsg::new(
+            msg.acknowledgement,
+            msg.original_packet,
+            msg.relayer,
+        )),
+    )
+    .unwrap();
+
+    assert!(resp
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "success" && attr.value == "true"));
+    assert_eq!(query_parent_transfers(&deps).transfers, vec![]);
+}
+
+#[cfg(feature = "cosmwasm_2")]
+#[test]
+fn remote_parent_transfer_timeout_should_release() {
+    let mut deps = remote_parent_setup();
+
+    let (packet, msg) = remote_parent_donate(&mut deps);
+    remote_parent_transfer_reply(&mut deps, &msg, 5);
+
+    let mut msg = mock_ibc_packet_timeout(IBC_CHANNEL, &packet).unwrap();
+    msg.packet.sequence = 5;
+    let err = ibc_source_callback(
+        deps.as_mut(),
+        mock_env(),
+        IbcSourceCallbackMsg::Timeout(IbcTimeoutCallbackMsg::new(msg.packet, msg.relayer)),
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::ParentTransferNotFoundErr {
+            channel: IBC_CHANNEL.to_owned(),
+            sequence: 5,
+        },
+        err
+    );
+
+    let mut msg = mock_ibc_packet_timeout(TRANSFER_CHANNEL, &packet).unwrap();
+    msg.packet.sequence = 5;
+    let resp = ibc_source_callback(
+        deps.as_mut(),
+        mock_env(),
+        IbcSourceCallbackMsg::Timeout(IbcTimeoutCallbackMsg::new(msg.packet, msg.relayer)),
+    )
+    .unwrap();
+
+    assert!(resp.messages.is_empty());
+    assert!(resp
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "error" && attr.value == "timeout"));
+    assert_eq!(query_parent_transfers(&deps).transfers, vec![]);
+}
+
+#[cfg(feature = "cosmwasm_2")]
+#[test]
+fn remote_parent_voucher_transfer_ack_should_work() {
+    const VOUCHER: &str = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+
+    let mut deps = remote_parent_setup();
+    set_contract_balance(&mut deps, vec![coin(100, ATOM), coin(50, VOUCHER)]);
+
+    let resp = execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender(), &coins(100, ATOM)),
+        ExecMsg::Donate { campaign: None },
+    )
+    .unwrap();
+
+    let msg = resp
+        .messages
+        .iter()
+        .find(|msg| match &msg.msg {
+            CosmosMsg::Ibc(IbcMsg::Transfer { amount, .. }) => amount.denom == VOUCHER,
+            _ => false,
+        })
+        .unwrap();
+    remote_parent_transfer_reply(&mut deps, msg, 300);
+
+    let transfers = query_parent_transfers(&deps).transfers;
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].sequence, 300);
+    assert_eq!(transfers[0].amount, coin(5, VOUCHER));
+
+    // The packet names the voucher by its denom trace, not by its ibc/ hash.
+    let packet = Ics20Packet {
+        denom: "transfer/channel-0/uatom".to_owned(),
+        amount: Uint128::new(5),
+        sender: mock_env().contract.address.to_string(),
+        receiver: remote_parent().to_string(),
+        memo: None,
+    };
+    let ack = to_json_binary(&IbcAck::Result(Binary::from(vec![1]))).unwrap();
+    let mut msg =
+        mock_ibc_packet_ack(TRANSFER_CHANNEL, &packet, IbcAcknowledgement::new(ack)).unwrap();
+    msg.original_packet.sequence = 300;
+
+    ibc_source_callback(
+        deps.as_mut(),
+        mock_env(),
+        IbcSourceCallbackMsg::Acknowledgement(IbcAckCallbackMsg::new(
+            msg.acknowledgement,
+            msg.original_packet,
+            msg.relayer,
+        )),
+    )
+    .unwrap();
+
+    assert_eq!(query_parent_transfers(&deps).transfers, vec![]);
+}
+
+fn instantiate_remote_parent(
+    parent_addr: &str,
+    allowed_prefixes: Vec<AllowedPrefix>,
+) -> Result<Response, ContractError> {
+    let mut deps = mock_dependencies();
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner(), &[]),
+        InstantiateMsg {
+            parent: Some(Parent {
+                addr: parent_addr.to_owned(),
+                donating_period: 1,
+                part: Decimal::percent(10),
+                remote: Some(RemoteParent {
+                    channel: TRANSFER_CHANNEL.to_owned(),
+                    timeout: 600,
+                }),
+            }),
+            allowed_prefixes,
+            ..zero_funds_instantiate_msg()
+        },
+    )
+}
+
+#[test]
+fn remote_parent_prefix_should_be_validated() {
+    let err = instantiate_remote_parent(remote_parent().as_str(), vec![]).unwrap_err();
+    assert_eq!(
+        ContractError::UnsupportedPrefixErr {
+            prefix: "inj".to_owned()
+        },
+        err
+    );
+
+    let err = instantiate_remote_parent(remote_parent().as_str(), vec![inj_prefix(vec![32])])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidAddressLengthErr {
+            address: remote_parent().to_string(),
+            length: 20
+        },
+        err
+    );
+
+    let address = "inj1g9v8suckezwx93zypckd4xg03r26h6ejlmsptq";
+    let err = instantiate_remote_parent(address, vec![inj_prefix(vec![20])]).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidBech32Err { .. }));
+
+    let address = remote_parent().as_str().to_uppercase();
+    let err = instantiate_remote_parent(&address, vec![inj_prefix(vec![20])]).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidBech32Err { .. }));
+
+    instantiate_remote_parent(remote_parent().as_str(), vec![inj_prefix(vec![20, 32])]).unwrap();
+}
+
+#[test]
+fn withdraw_to_remote_address_should_work() {
+    let mut deps = mock_dependencies();
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner(), &[]),
+        zero_funds_instantiate_msg(),
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner(), &[]),
+        ExecMsg::SetAllowedPrefixes {
+            prefixes: vec![inj_prefix(vec![20])],
+        },
+    )
+    .unwrap();
+
+    set_contract_balance(&mut deps, coins(100, ATOM));
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner(), &[]),
+        ExecMsg::WithdrawTo {
+            receiver: remote_parent().to_string(),
+            funds: vec![],
+            cw20_funds: vec![Cw20Coin {
+                address: "token".to_owned(),
+                amount: Uint128::new(10),
+            }],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::RemoteCw20Err {
+            address: remote_parent().to_string()
+        },
+        err
+    );
+
+    let resp = execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner(), &[]),
+        ExecMsg::WithdrawTo {
+            receiver: remote_parent().to_string(),
+            funds: vec![],
+            cw20_funds: vec![],
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        resp.messages[0].msg,
+        CosmosMsg::Ibc(IbcMsg::Transfer {
+            channel_id: TRANSFER_CHANNEL.to_owned(),
+            to_address: remote_parent().to_string(),
+            amount: coin(100, ATOM),
+            timeout: IbcTimeout::with_timestamp(mock_env().block.time.plus_seconds(60 * 60)),
+            #[cfg(feature = "cosmwasm_2")]
+            memo: None,
+        })
+    );
+
+    let resp = execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner(), &[]),
+        ExecMsg::WithdrawTo {
+            receiver: sender().to_string(),
+            funds: coins(10, ATOM),
+            cw20_funds: vec![],
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        resp.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: sender().to_string(),
+            amount: coins(10, ATOM),
+        })
+    );
+
+    let resp = execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner(), &[]),
+        ExecMsg::WithdrawSplit {
+            recipients: vec![
+                (remote_parent().to_string(), Decimal::one()),
+                (sender().to_string(), Decimal::one()),
+            ],
+            funds: coins(10, ATOM),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        resp.messages[0].msg,
+        CosmosMsg::Ibc(IbcMsg::Transfer {
+            channel_id: TRANSFER_CHANNEL.to_owned(),
+            to_address: remote_parent().to_string(),
+            amount: coin(5, ATOM),
+            timeout: IbcTimeout::with_timestamp(mock_env().block.time.plus_seconds(60 * 60)),
+            #[cfg(feature = "cosmwasm_2")]
+            memo: None,
+        })
+    );
+    assert_eq!(
+        resp.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: sender().to_string(),
+            amount: coins(5, ATOM),
+        })
+    );
+}
+
+#[test]
+#[cfg(not(feature = "cosmwasm_2"))]
+fn migrate_should_work() {
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender(), coins(10, ATOM))
+            .unwrap();
+    });
+
+    let old_code_id = CountingContract_0_1::store_code(&mut app);
+    let new_code_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract_0_1::instantiate_with_funds(
         &mut app,
-        code_id,
+        old_code_id,
         owner(),
         COUNTING_LABEL,
         0,
-        ten_atom(),
+        zero_atom(),
         &[],
-        Some(owner().to_string()),
-        Parent {
-            addr: parent_contract.addr().to_string(),
-            donating_period: 2,
-            part: Decimal::percent(10),
-        },
+        other_sender(),
     )
     .unwrap();
 
     contract
-        .donate(&mut app, sender(), vec![ten_atom()].as_slice())
+        .donate(&mut app, sender(), &coins(10, ATOM))
+        .unwrap();
+
+    let contract =
+        CountingContract::migrate(&mut app, contract.addr(), new_code_id, other_sender(), None)
+            .unwrap();
+
+    let resp = contract.query_value(&app).unwrap();
+    assert_eq!(resp.value, 1);
+
+    let state = STATE.query(&app.wrap(), contract.addr()).unwrap();
+
+    assert_eq!(state, State::new(1, zero_atom(), owner(), None));
+
+    assert_eq!(
+        contract.query_storage_keys(&app).unwrap().keys,
+        vec!["contract_info".to_owned(), "state".to_owned()]
+    );
+}
+
+#[test]
+fn migrate_no_update_should_works() {
+    let CountingSuite {
+        mut app,
+        code_id,
+        contract,
+        ..
+    } = CountingSuiteBuilder::new()
+        .with_balance(&sender(), &coins(10, ATOM))
+        .with_owner(&sender())
+        .with_minimal_donation(ten_atom())
+        .with_admin(&owner())
+        .build()
         .unwrap();
 
     contract
         .donate(&mut app, sender(), vec![ten_atom()].as_slice())
         .unwrap();
 
-    // let resp = parent_contract.query_value(&app).unwrap();
-    // assert_eq!(resp.value, 1);
+    let contract =
+        CountingContract::migrate(&mut app, contract.addr(), code_id, owner(), None).unwrap();
 
     let resp = contract.query_value(&app).unwrap();
-    assert_eq!(resp.value, 2);
+    assert_eq!(resp.value, 1);
+
+    let state = STATE.query(&app.wrap(), contract.addr()).unwrap();
+    assert_eq!(state, State::new(1, ten_atom(), sender(), None))
+}
+
+#[test]
+fn migration_steps_should_be_chained() {
+    for steps in MIGRATIONS.windows(2) {
+        assert_eq!(steps[0].to, steps[1].from);
+    }
+
+    for step in MIGRATIONS {
+        assert!(parse_version(step.from).unwrap() < parse_version(step.to).unwrap());
+    }
+}
+
+#[test]
+fn migration_path_should_work() {
+    let path = |from: &str, to: &str| {
+        migration_path(&parse_version(from).unwrap(), &parse_version(to).unwrap())
+            .map(|steps| steps.iter().map(|step| step.from).collect::<Vec<_>>())
+    };
+
+    assert_eq!(path("0.1.0", "0.3.0").unwrap(), vec!["0.1.0", "0.2.0"]);
+    assert_eq!(path("0.2.0", "0.3.0").unwrap(), vec!["0.2.0"]);
+    assert_eq!(path("0.3.0-rc.1", "0.3.0").unwrap(), vec!["0.2.0"]);
+    assert_eq!(path("0.3.0", "0.3.1").unwrap(), Vec::<&str>::new());
 
-    assert_eq!(app.wrap().query_all_balances(owner()).unwrap(), vec![]);
-    assert_eq!(app.wrap().query_all_balances(sender()).unwrap(), vec![]);
     assert_eq!(
-        app.wrap().query_all_balances(contract.addr()).unwrap(),
-        coins(18, ATOM)
+        path("0.3.0", "0.2.0").unwrap_err(),
+        ContractError::DowngradeErr {
+            from: "0.3.0".to_owned(),
+            to: "0.2.0".to_owned()
+        }
     );
     assert_eq!(
-        app.wrap()
-            .query_all_balances(parent_contract.addr())
-            .unwrap(),
-        coins(2, ATOM)
+        path("0.0.1", "0.3.0").unwrap_err(),
+        ContractError::InvalidVersion {
+            expected: "0.3.0".to_owned(),
+            found: "0.0.1".to_owned()
+        }
+    );
+}
+
+#[test]
+fn migration_steps_should_convert_storage() {
+    let mut storage = MockStorage::new();
+
+    Item::<u64>::new("counter").save(&mut storage, &3).unwrap();
+    Item::<Coin>::new("donation")
+        .save(&mut storage, &ten_atom())
+        .unwrap();
+    Item::<Addr>::new("owner")
+        .save(&mut storage, &owner())
+        .unwrap();
+
+    migrate_0_1_0(&mut storage).unwrap();
+
+    assert!(Item::<u64>::new("counter")
+        .may_load(&storage)
+        .unwrap()
+        .is_none());
+    assert!(Item::<Coin>::new("donation")
+        .may_load(&storage)
+        .unwrap()
+        .is_none());
+    assert!(Item::<Addr>::new("owner")
+        .may_load(&storage)
+        .unwrap()
+        .is_none());
+
+    migrate_0_2_0(&mut storage).unwrap();
+
+    assert_eq!(
+        STATE.load(&storage).unwrap(),
+        State::new(3, ten_atom(), owner(), None)
+    );
+}
+
+#[test]
+fn migrate_downgrade_should_fail() {
+    let mut deps = mock_dependencies();
+
+    cw2::set_contract_version(&mut deps.storage, "counting-contract", "9.0.0").unwrap();
+
+    let err = crate::migrate(deps.as_mut(), mock_env(), MigrateMsg::new(None)).unwrap_err();
+    assert_eq!(
+        ContractError::DowngradeErr {
+            from: "9.0.0".to_owned(),
+            to: env!("CARGO_PKG_VERSION").to_owned()
+        },
+        err
+    );
+
+    cw2::set_contract_version(&mut deps.storage, "counting-contract", "latest").unwrap();
+
+    let err = crate::migrate(deps.as_mut(), mock_env(), MigrateMsg::new(None)).unwrap_err();
+    assert_eq!(
+        ContractError::InvalidVersion {
+            expected: env!("CARGO_PKG_VERSION").to_owned(),
+            found: "latest".to_owned()
+        },
+        err
+    );
+}
+
+#[test]
+fn migrate_name_should_be_checked() {
+    let mut deps = legacy_0_1_0_deps(owner());
+
+    cw2::set_contract_version(&mut deps.storage, "other-contract", "0.1.0").unwrap();
+
+    let err = crate::migrate(deps.as_mut(), mock_env(), MigrateMsg::new(None)).unwrap_err();
+    assert_eq!(
+        ContractError::InvalidName {
+            expected: "counting-contract".to_owned(),
+            found: "other-contract".to_owned()
+        },
+        err
+    );
+
+    cw2::set_contract_version(&mut deps.storage, "counting-contract-v0", "0.1.0").unwrap();
+
+    crate::migrate(deps.as_mut(), mock_env(), MigrateMsg::new(None)).unwrap();
+
+    let contract = cw2::get_contract_version(&deps.storage).unwrap();
+    assert_eq!(contract.contract, "counting-contract");
+    assert_eq!(contract.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(
+        STATE.load(&deps.storage).unwrap(),
+        State::new(3, ten_atom(), owner(), None)
+    );
+
+    cw2::set_contract_version(
+        &mut deps.storage,
+        "counting-contract-v0",
+        env!("CARGO_PKG_VERSION"),
+    )
+    .unwrap();
+
+    crate::migrate(deps.as_mut(), mock_env(), MigrateMsg::new(None)).unwrap();
+
+    let contract = cw2::get_contract_version(&deps.storage).unwrap();
+    assert_eq!(contract.contract, "counting-contract");
+}
+
+fn legacy_0_1_0_deps(owner: Addr) -> MockDeps {
+    let mut deps = mock_dependencies();
+
+    cw2::set_contract_version(&mut deps.storage, "counting-contract", "0.1.0").unwrap();
+    Item::<u64>::new("counter")
+        .save(&mut deps.storage, &3)
+        .unwrap();
+    Item::<Coin>::new("donation")
+        .save(&mut deps.storage, &ten_atom())
+        .unwrap();
+    Item::<Addr>::new("owner")
+        .save(&mut deps.storage, &owner)
+        .unwrap();
+
+    deps
+}
+
+#[test]
+fn migration_preview_should_work() {
+    let mut deps = legacy_0_1_0_deps(owner());
+
+    let resp = query(deps.as_ref(), mock_env(), QueryMsg::MigrationPreview {}).unwrap();
+    let preview: MigrationPreviewResp = from_json(&resp).unwrap();
+
+    assert_eq!(preview.stored_version, "0.1.0");
+    assert_eq!(preview.target_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(
+        preview.steps,
+        vec![
+            MigrationStepResp {
+                from: "0.1.0".to_owned(),
+                to: "0.2.0".to_owned()
+            },
+            MigrationStepResp {
+                from: "0.2.0".to_owned(),
+                to: "0.3.0".to_owned()
+            },
+        ]
+    );
+    assert_eq!(
+        preview.stored_state,
+        StoredState {
+            counter: 3,
+            minimal_donation: ten_atom(),
+            owner: owner(),
+        }
+    );
+
+    crate::migrate(deps.as_mut(), mock_env(), MigrateMsg::new(None)).unwrap();
+
+    let resp = query(deps.as_ref(), mock_env(), QueryMsg::MigrationPreview {}).unwrap();
+    let preview: MigrationPreviewResp = from_json(&resp).unwrap();
+
+    assert_eq!(preview.stored_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(preview.steps, vec![]);
+    assert_eq!(preview.stored_state.counter, 3);
+}
+
+#[test]
+fn migrate_invariants_should_be_checked() {
+    let mut deps = legacy_0_1_0_deps(Addr::unchecked("ab"));
+
+    let err = crate::migrate(deps.as_mut(), mock_env(), MigrateMsg::new(None)).unwrap_err();
+    assert_eq!(
+        ContractError::MigrationInvariantErr {
+            reason: "owner ab is not valid".to_owned()
+        },
+        err
+    );
+
+    let mut deps = legacy_0_1_0_deps(owner());
+
+    let err = crate::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg::new(Some(Parent {
+            addr: parent().to_string(),
+            donating_period: 0,
+            part: Decimal::percent(10),
+            remote: None,
+        })),
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::MigrationInvariantErr {
+            reason: "parent countdown 0 doesn't fit period 0".to_owned()
+        },
+        err
+    );
+}
+
+#[test]
+fn migrate_overrides_should_require_failed_path() {
+    let mut deps = legacy_0_1_0_deps(owner());
+
+    let err = crate::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            force_from_version: Some("0.1.0".to_owned()),
+            ..MigrateMsg::new(None)
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::MigrationOverrideErr {
+            reason: "version 0.1.0 migrates without overrides".to_owned()
+        },
+        err
+    );
+
+    cw2::set_contract_version(&mut deps.storage, "counting-contract", "fork").unwrap();
+
+    let err = crate::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            force_from_version: Some("0.1.0".to_owned()),
+            state_override: Some(StateOverride {
+                counter: 3,
+                minimal_donation: ten_atom(),
+                owner: owner().to_string(),
+            }),
+            ..MigrateMsg::new(None)
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::MigrationOverrideErr {
+            reason: "force_from_version and state_override can't be combined".to_owned()
+        },
+        err
+    );
+}
+
+#[test]
+fn migrate_forced_version_should_work() {
+    let mut deps = legacy_0_1_0_deps(owner());
+    cw2::set_contract_version(&mut deps.storage, "counting-contract", "0.1.0-fork").unwrap();
+
+    let err = crate::migrate(deps.as_mut(), mock_env(), MigrateMsg::new(None)).unwrap_err();
+    assert_eq!(
+        ContractError::InvalidVersion {
+            expected: env!("CARGO_PKG_VERSION").to_owned(),
+            found: "0.1.0-fork".to_owned()
+        },
+        err
+    );
+
+    crate::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            force_from_version: Some("0.1.0".to_owned()),
+            ..MigrateMsg::new(None)
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        STATE.load(&deps.storage).unwrap(),
+        State::new(3, ten_atom(), owner(), None)
+    );
+    assert_eq!(
+        cw2::get_contract_version(&deps.storage).unwrap().version,
+        env!("CARGO_PKG_VERSION")
+    );
+}
+
+#[test]
+fn migrate_state_override_should_work() {
+    let mut deps = mock_dependencies();
+    cw2::set_contract_version(&mut deps.storage, "counting-contract", "unpublished").unwrap();
+    Item::<u64>::new("fork_counter")
+        .save(&mut deps.storage, &5)
+        .unwrap();
+
+    let err = crate::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            force_from_version: Some("0.2.0".to_owned()),
+            ..MigrateMsg::new(None)
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::NotFound { .. }));
+
+    crate::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            state_override: Some(StateOverride {
+                counter: 5,
+                minimal_donation: ten_atom(),
+                owner: owner().to_string(),
+            }),
+            ..MigrateMsg::new(Some(Parent {
+                addr: parent().to_string(),
+                donating_period: 2,
+                part: Decimal::percent(10),
+                remote: None,
+            }))
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        STATE.load(&deps.storage).unwrap(),
+        State::new(5, ten_atom(), owner(), Some(2))
+    );
+    assert_eq!(
+        cw2::get_contract_version(&deps.storage).unwrap().version,
+        env!("CARGO_PKG_VERSION")
+    );
+}
+
+#[test]
+fn migrate_state_override_without_contract_info_should_work() {
+    let mut deps = mock_dependencies();
+    let state_override = StateOverride {
+        counter: 7,
+        minimal_donation: ten_atom(),
+        owner: owner().to_string(),
+    };
+
+    let err = crate::migrate(deps.as_mut(), mock_env(), MigrateMsg::new(None)).unwrap_err();
+    assert!(matches!(err, ContractError::NotFound { .. }));
+
+    crate::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            state_override: Some(state_override.clone()),
+            ..MigrateMsg::new(None)
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        STATE.load(&deps.storage).unwrap(),
+        State::new(7, ten_atom(), owner(), None)
+    );
+    let contract = cw2::get_contract_version(&deps.storage).unwrap();
+    assert_eq!(contract.contract, "counting-contract");
+    assert_eq!(contract.version, env!("CARGO_PKG_VERSION"));
+
+    // A fork published under its own name is taken over the same way.
+    cw2::set_contract_version(&mut deps.storage, "forked-counter", "1.0.0").unwrap();
+    crate::migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            state_override: Some(state_override),
+            ..MigrateMsg::new(None)
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        cw2::get_contract_version(&deps.storage).unwrap().contract,
+        "counting-contract"
+    );
+}
+
+#[test]
+fn missing_state_should_be_not_found() {
+    let mut deps = mock_dependencies();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender(), &[]),
+        ExecMsg::Increment { value: 1 },
+    )
+    .unwrap_err();
+
+    let ContractError::NotFound { item } = &err else {
+        panic!("unexpected error: {err}");
+    };
+    assert!(item.contains("counting_contract::state::State"));
+    assert_eq!(err.code(), 1001);
+    assert!(err.to_string().starts_with("[1001] "));
+}
+
+#[test]
+fn failed_parent_payout_should_be_reported() {
+    let mut deps = mock_dependencies();
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner(), &[]),
+        InstantiateMsg::new(
+            0,
+            ten_atom(),
+            Some(Parent {
+                addr: parent().to_string(),
+                donating_period: 1,
+                part: Decimal::percent(10),
+                remote: None,
+            }),
+        ),
+    )
+    .unwrap();
+
+    // The mock querier knows no contracts, so the cw20 balance query fails.
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&owner(), &[]),
+        ExecMsg::AllowCw20 {
+            token: other_sender().to_string(),
+            minimal_donation: Uint128::new(10),
+        },
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender(), &[ten_atom()]),
+        ExecMsg::Donate { campaign: None },
+    )
+    .unwrap_err();
+
+    let ContractError::ParentPayoutFailed {
+        parent: addr,
+        reason,
+    } = &err
+    else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(addr, parent().as_str());
+    assert!(reason.starts_with("[1002] Querier failed"));
+    assert_eq!(err.code(), 1403);
+    assert!(err.to_string().starts_with("[1403] "));
+}
+
+#[test]
+fn donate_parent_should_works() {
+    let mut suite = CountingSuiteBuilder::new()
+        .with_balance(&sender(), &coins(20, ATOM))
+        .with_minimal_donation(ten_atom())
+        .with_admin(&owner())
+        .with_parent(ten_atom(), 2, Decimal::percent(10))
+        .build()
+        .unwrap();
+
+    let resps = suite.donate_times(&sender(), 2, &[ten_atom()]).unwrap();
+
+    assert_eq!(suite.value().unwrap(), 2);
+
+    let donate = |contract: &CountingContract, sender: Addr| {
+        EventMatcher::wasm()
+            .contract(&contract.addr())
+            .action("donate")
+            .attr("sender", sender)
+    };
+    assert_events(&resps[0].events)
+        .contains(donate(&suite.contract, sender()))
+        .not_contains(EventMatcher::wasm().contract(&suite.parent().addr()));
+    assert_events(&resps[1].events).in_order([
+        donate(&suite.contract, sender()),
+        donate(suite.parent(), suite.contract.addr()),
+    ]);
+
+    assert_eq!(suite.balance(&owner(), ATOM).unwrap(), coin(0, ATOM));
+    assert_eq!(suite.balance(&sender(), ATOM).unwrap(), coin(0, ATOM));
+    assert_eq!(
+        suite.balance(&suite.contract.addr(), ATOM).unwrap(),
+        coin(18, ATOM)
+    );
+    assert_eq!(
+        suite.balance(&suite.parent().addr(), ATOM).unwrap(),
+        coin(2, ATOM)
+    );
+}
+
+#[test]
+fn suite_parent_chain_should_work() {
+    let mut suite = CountingSuiteBuilder::new()
+        .with_balance(&sender(), &coins(10, ATOM))
+        .with_parent(zero_atom(), 1, Decimal::percent(50))
+        .with_parent(zero_atom(), 1, Decimal::percent(50))
+        .build()
+        .unwrap();
+
+    suite.donate(&sender(), &coins(10, ATOM)).unwrap();
+
+    assert_eq!(
+        suite.balance(&suite.contract.addr(), ATOM).unwrap(),
+        coin(5, ATOM)
+    );
+    assert_eq!(
+        suite.balance(&suite.parents[0].addr(), ATOM).unwrap(),
+        coin(3, ATOM)
+    );
+    assert_eq!(
+        suite.balance(&suite.parents[1].addr(), ATOM).unwrap(),
+        coin(2, ATOM)
     );
+    assert_eq!(suite.parents[1].query_value(&suite.app).unwrap().value, 1);
+
+    let block = suite.app.block_info();
+    suite.advance_blocks(10);
+    assert_eq!(suite.app.block_info().height, block.height + 10);
+    assert_eq!(suite.app.block_info().time, block.time.plus_seconds(50));
 }
 
 #[test]
+#[cfg(not(feature = "cosmwasm_2"))]
 fn migrate_parent_should_works() {
     let mut app = App::new(|router, _api, storage| {
         router
@@ -595,6 +3849,7 @@ fn migrate_parent_should_works() {
             addr: parent().to_string(),
             donating_period: 2,
             part: Decimal::percent(10),
+            remote: None,
         },
     )
     .unwrap();
@@ -614,6 +3869,59 @@ fn migrate_parent_should_works() {
             address: parent(),
             donating_parent_period: 2,
             part: Decimal::percent(10),
+            remote: None,
         }
     )
 }
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn random_calls_should_match_model(
+        minimal_donation in 0..=10u128,
+        donating_period in 1..=3u64,
+        part in 0..=100u64,
+        calls in prop_vec(model::call(), 1..40),
+    ) {
+        let mut suite = CountingSuiteBuilder::new()
+            .with_balance(&sender(), &model::initial_funds())
+            .with_balance(&other_sender(), &model::initial_funds())
+            .with_minimal_donation(coin(minimal_donation, ATOM))
+            .with_parent(zero_atom(), donating_period, Decimal::percent(part))
+            .build()
+            .unwrap();
+        let mut model = Model::new(&suite, minimal_donation, donating_period, part);
+
+        for call in calls {
+            let before = Snapshot::take(&suite);
+            let result = suite.app.execute_contract(
+                call.sender.clone(),
+                suite.contract.addr(),
+                &call.msg,
+                &call.funds,
+            );
+            let after = Snapshot::take(&suite);
+
+            let authorized = model.is_authorized(&call);
+            let accepted = model.apply(&call);
+            prop_assert_eq!(result.is_ok(), accepted, "{:?}: {:?}", call, result);
+
+            if !authorized {
+                let err = MultitestError::from(result.unwrap_err());
+                prop_assert_eq!(
+                    err,
+                    ContractError::UnauthorizedErr {
+                        owner: owner().to_string()
+                    }
+                );
+                prop_assert_eq!(&before, &after);
+            }
+
+            prop_assert_eq!(&after, &model.state);
+            for denom in model::DENOMS {
+                prop_assert_eq!(after.total(denom), 2 * model::INITIAL_FUNDS);
+            }
+        }
+    }
+}
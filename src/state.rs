@@ -1,7 +1,10 @@
-use cosmwasm_std::{Addr, Coin, Decimal};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, Coin, Decimal, IbcEndpoint, StdResult, Timestamp, Uint128};
+use cw20::Cw20Coin;
+use cw_storage_plus::{Item, Map};
 use serde::{Deserialize, Serialize};
 
+use crate::msg::{CampaignStatus, RemoteParent};
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct State {
     pub counter: u64,
@@ -31,17 +34,345 @@ pub struct ParentDonation {
     pub address: Addr,
     pub donating_parent_period: u64,
     pub part: Decimal,
+    #[serde(default)]
+    pub remote: Option<RemoteParent>,
 }
 
 impl ParentDonation {
-    pub fn new(address: Addr, donating_parent_period: u64, part: Decimal) -> Self {
+    pub fn new(
+        address: Addr,
+        donating_parent_period: u64,
+        part: Decimal,
+        remote: Option<RemoteParent>,
+    ) -> Self {
         Self {
             address,
             donating_parent_period,
             part,
+            remote,
+        }
+    }
+}
+
+#[cfg(feature = "cosmwasm_2")]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ParentTransfer {
+    pub channel: String,
+    pub receiver: String,
+    pub amount: Coin,
+    pub timeout: Timestamp,
+}
+
+#[cfg(feature = "cosmwasm_2")]
+impl ParentTransfer {
+    pub fn new(channel: String, receiver: String, amount: Coin, timeout: Timestamp) -> Self {
+        Self {
+            channel,
+            receiver,
+            amount,
+            timeout,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct WithdrawalConfig {
+    pub delay: u64,
+    pub guardian: Option<Addr>,
+}
+
+impl WithdrawalConfig {
+    pub fn new(delay: u64, guardian: Option<Addr>) -> Self {
+        Self { delay, guardian }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PendingWithdrawalConfig {
+    pub config: WithdrawalConfig,
+    pub effective_at: Timestamp,
+}
+
+impl PendingWithdrawalConfig {
+    pub fn new(config: WithdrawalConfig, effective_at: Timestamp) -> Self {
+        Self {
+            config,
+            effective_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct PendingWithdrawal {
+    pub receiver: Addr,
+    pub funds: Vec<Coin>,
+    #[serde(default)]
+    pub cw20_funds: Vec<Cw20Coin>,
+    pub release_at: Timestamp,
+}
+
+impl PendingWithdrawal {
+    pub fn new(
+        receiver: Addr,
+        funds: Vec<Coin>,
+        cw20_funds: Vec<Cw20Coin>,
+        release_at: Timestamp,
+    ) -> Self {
+        Self {
+            receiver,
+            funds,
+            cw20_funds,
+            release_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Stream {
+    pub beneficiary: Addr,
+    pub denom: String,
+    pub amount_per_period: Uint128,
+    pub period: u64,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub claimed: Uint128,
+}
+
+impl Stream {
+    pub fn new(
+        beneficiary: Addr,
+        denom: String,
+        amount_per_period: Uint128,
+        period: u64,
+        start_time: Timestamp,
+        end_time: Timestamp,
+    ) -> Self {
+        Self {
+            beneficiary,
+            denom,
+            amount_per_period,
+            period,
+            start_time,
+            end_time,
+            claimed: Uint128::zero(),
+        }
+    }
+
+    pub fn vested(&self, now: Timestamp) -> StdResult<Uint128> {
+        let now = std::cmp::min(now, self.end_time);
+
+        if now <= self.start_time {
+            return Ok(Uint128::zero());
+        }
+
+        let periods = (now.seconds() - self.start_time.seconds()) / self.period;
+        let vested = self.amount_per_period.checked_mul(Uint128::from(periods))?;
+
+        Ok(vested)
+    }
+
+    pub fn unclaimed(&self) -> StdResult<Uint128> {
+        Ok(self.vested(self.end_time)?.saturating_sub(self.claimed))
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Matching {
+    pub denom: String,
+    pub ratio: Decimal,
+    pub per_donor_cap: Uint128,
+    pub global_cap: Uint128,
+    pub ends_at: Timestamp,
+    pub round: u64,
+    pub pool: Uint128,
+    pub matched: Uint128,
+}
+
+impl Matching {
+    pub fn new(
+        denom: String,
+        ratio: Decimal,
+        per_donor_cap: Uint128,
+        global_cap: Uint128,
+        ends_at: Timestamp,
+        round: u64,
+    ) -> Self {
+        Self {
+            denom,
+            ratio,
+            per_donor_cap,
+            global_cap,
+            ends_at,
+            round,
+            pool: Uint128::zero(),
+            matched: Uint128::zero(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Campaign {
+    pub targets: Vec<Coin>,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub refundable: bool,
+    pub raised: Vec<Coin>,
+    pub refunded: Vec<Coin>,
+}
+
+impl Campaign {
+    pub fn new(
+        targets: Vec<Coin>,
+        start_time: Timestamp,
+        end_time: Timestamp,
+        refundable: bool,
+    ) -> Self {
+        Self {
+            targets,
+            start_time,
+            end_time,
+            refundable,
+            raised: vec![],
+            refunded: vec![],
+        }
+    }
+
+    pub fn status(&self, now: Timestamp) -> CampaignStatus {
+        if now < self.start_time {
+            return CampaignStatus::Upcoming;
+        }
+
+        if now < self.end_time {
+            return CampaignStatus::Active;
+        }
+
+        let goal_met = self.targets.iter().all(|target| {
+            self.raised
+                .iter()
+                .any(|coin| coin.denom == target.denom && coin.amount >= target.amount)
+        });
+
+        if goal_met {
+            CampaignStatus::Succeeded
+        } else {
+            CampaignStatus::Failed
+        }
+    }
+
+    // Funds of a refundable campaign cannot be withdrawn unless it succeeds.
+    pub fn locked(&self, now: Timestamp) -> Vec<Coin> {
+        if !self.refundable || self.status(now) == CampaignStatus::Succeeded {
+            return vec![];
+        }
+
+        self.raised
+            .iter()
+            .map(|coin| {
+                let refunded = self
+                    .refunded
+                    .iter()
+                    .find(|c| c.denom == coin.denom)
+                    .map(|c| c.amount)
+                    .unwrap_or_default();
+
+                Coin::new(coin.amount.saturating_sub(refunded).u128(), &coin.denom)
+            })
+            .filter(|coin| !coin.amount.is_zero())
+            .collect()
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Donation {
+    pub donor: Addr,
+    pub funds: Vec<Coin>,
+    pub donated_at: Timestamp,
+    pub counted: bool,
+    pub payouts_before: u64,
+    pub campaign: Option<u64>,
+    #[serde(default)]
+    pub matched: Uint128,
+    #[serde(default)]
+    pub matching_round: u64,
+}
+
+impl Donation {
+    pub fn new(
+        donor: Addr,
+        funds: Vec<Coin>,
+        donated_at: Timestamp,
+        counted: bool,
+        payouts_before: u64,
+        campaign: Option<u64>,
+    ) -> Self {
+        Self {
+            donor,
+            funds,
+            donated_at,
+            counted,
+            payouts_before,
+            campaign,
+            matched: Uint128::zero(),
+            matching_round: 0,
         }
     }
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct IbcDonation {
+    pub donor: Addr,
+    pub channel: String,
+    pub funds: Vec<Coin>,
+}
+
+impl IbcDonation {
+    pub fn new(donor: Addr, channel: String, funds: Vec<Coin>) -> Self {
+        Self {
+            donor,
+            channel,
+            funds,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct AddressPrefix {
+    pub lengths: Vec<u32>,
+    pub channel: String,
+}
+
+impl AddressPrefix {
+    pub fn new(lengths: Vec<u32>, channel: String) -> Self {
+        Self { lengths, channel }
+    }
+}
+
 pub const STATE: Item<State> = Item::new("state");
 pub const PARENT_DONATION: Item<ParentDonation> = Item::new("parent_donation");
+pub const PARENT_PAYOUTS: Item<u64> = Item::new("parent_payouts");
+pub const REFUND_GRACE_PERIOD: Item<u64> = Item::new("refund_grace_period");
+pub const NEXT_DONATION_ID: Item<u64> = Item::new("next_donation_id");
+pub const DONATIONS: Map<u64, Donation> = Map::new("donations");
+pub const WITHDRAWAL_CONFIG: Item<WithdrawalConfig> = Item::new("withdrawal_config");
+pub const PENDING_WITHDRAWAL_CONFIG: Item<PendingWithdrawalConfig> =
+    Item::new("pending_withdrawal_config");
+pub const NEXT_WITHDRAWAL_ID: Item<u64> = Item::new("next_withdrawal_id");
+pub const PENDING_WITHDRAWALS: Map<u64, PendingWithdrawal> = Map::new("pending_withdrawals");
+pub const RECEIPT_CONTRACT: Item<Addr> = Item::new("receipt_contract");
+pub const RECEIPT_COUNT: Item<u64> = Item::new("receipt_count");
+pub const NEXT_CAMPAIGN_ID: Item<u64> = Item::new("next_campaign_id");
+pub const CAMPAIGNS: Map<u64, Campaign> = Map::new("campaigns");
+pub const CAMPAIGN_DONATIONS: Map<(u64, &Addr), Vec<Coin>> = Map::new("campaign_donations");
+pub const MATCHING: Item<Matching> = Item::new("matching");
+pub const MATCHED_DONORS: Map<(u64, &Addr), Uint128> = Map::new("matched_donors");
+pub const CW20_TOKENS: Map<&Addr, Uint128> = Map::new("cw20_tokens");
+pub const NEXT_STREAM_ID: Item<u64> = Item::new("next_stream_id");
+pub const STREAMS: Map<u64, Stream> = Map::new("streams");
+pub const IBC_CHANNELS: Map<&str, IbcEndpoint> = Map::new("ibc_channels");
+pub const NEXT_IBC_DONATION_ID: Item<u64> = Item::new("next_ibc_donation_id");
+pub const IBC_DONATIONS: Map<u64, IbcDonation> = Map::new("ibc_donations");
+#[cfg(feature = "cosmwasm_2")]
+// Keyed by the transfer channel and packet sequence.
+#[cfg(feature = "cosmwasm_2")]
+pub const PARENT_TRANSFERS: Map<(&str, u64), ParentTransfer> = Map::new("parent_transfers");
+pub const ADDRESS_PREFIXES: Map<&str, AddressPrefix> = Map::new("address_prefixes");